@@ -1,3 +1,5 @@
+pub use csta_core::lattice::*;
+pub use csta_core::neighbours::*;
 pub use csta_core::vec2::*;
 pub use csta_core::vec3::*;
 pub use csta_core::vec4::*;
@@ -5,6 +7,8 @@ pub use csta_core::vec4::*;
 pub use csta_metropolis::*;
 pub use csta_montecarlo::*;
 
+pub use csta_models as models;
+
 pub use csta_derive;
 
 pub mod prelude;
@@ -1,9 +1,26 @@
+pub use csta_core::lattice::*;
+pub use csta_core::neighbours::*;
 pub use csta_core::vec2::*;
 pub use csta_core::vec3::*;
 pub use csta_core::vec4::*;
 
+pub use csta_metropolis::acceptance::*;
+pub use csta_metropolis::annealing::*;
+pub use csta_metropolis::builder::*;
+pub use csta_metropolis::chains::*;
+pub use csta_metropolis::ensembles::*;
+#[cfg(feature = "serde")]
+pub use csta_metropolis::checkpoint::*;
+pub use csta_metropolis::cluster::*;
+pub use csta_metropolis::moves::*;
+pub use csta_metropolis::observer::sink::*;
 pub use csta_metropolis::observer::*;
+pub use csta_metropolis::tempering::*;
+pub use csta_metropolis::thermalization::*;
+pub use csta_metropolis::tuning::*;
+pub use csta_metropolis::wang_landau::*;
 pub use csta_metropolis::*;
+pub use csta_montecarlo::seed::SeedRng;
 pub use csta_montecarlo::*;
 
 pub use csta_derive;
@@ -0,0 +1,246 @@
+//! Lattices and graphs of sites, with precomputed neighbour tables.
+//! Sites are numbered 0..len, and every bond is listed from both of its ends.
+//!
+
+/// What happens to the bonds that cross the edge of a lattice
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// they wrap around to the other side
+    #[default]
+    Periodic,
+    /// they are dropped, sites on the edge have less neighbours
+    Open,
+}
+
+/// Undirected graph, for arbitrary networks.
+/// Self loops are dropped, repeated bonds are kept (each one counts).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Graph {
+    // neighbours of site i are neighbours[offsets[i]..offsets[i + 1]]
+    offsets: Vec<usize>,
+    neighbours: Vec<usize>,
+}
+
+impl Graph {
+    /// Graph of `len` sites from its bonds, each one given once.
+    /// Panics if a bond has a site out of range.
+    pub fn from_edges(len: usize, edges: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut adjacency = vec![Vec::new(); len];
+        for (a, b) in edges {
+            assert!(a < len && b < len, "bond ({a}, {b}) out of {len} sites");
+            if a != b {
+                adjacency[a].push(b);
+                adjacency[b].push(a);
+            }
+        }
+        Self::from_adjacency(adjacency)
+    }
+
+    /// Graph from the neighbours of each site, which have to be symmetric
+    /// (if j is a neighbour of i, i is a neighbour of j).
+    pub fn from_adjacency(adjacency: Vec<Vec<usize>>) -> Self {
+        let mut offsets = Vec::with_capacity(adjacency.len() + 1);
+        offsets.push(0);
+        let mut neighbours = Vec::new();
+        for (site, list) in adjacency.into_iter().enumerate() {
+            neighbours.extend(list.into_iter().filter(|&j| j != site));
+            offsets.push(neighbours.len());
+        }
+        Self {
+            offsets,
+            neighbours,
+        }
+    }
+
+    /// Number of sites
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn neighbours(&self, site: usize) -> &[usize] {
+        &self.neighbours[self.offsets[site]..self.offsets[site + 1]]
+    }
+
+    pub fn degree(&self, site: usize) -> usize {
+        self.offsets[site + 1] - self.offsets[site]
+    }
+
+    /// Every bond once, as (a, b) with a < b
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.len()).flat_map(move |a| {
+            self.neighbours(a)
+                .iter()
+                .filter(move |&&b| a < b)
+                .map(move |&b| (a, b))
+        })
+    }
+
+    /// Number of bonds
+    pub fn edge_count(&self) -> usize {
+        self.neighbours.len() / 2
+    }
+}
+
+impl From<Lattice> for Graph {
+    fn from(lattice: Lattice) -> Self {
+        lattice.graph
+    }
+}
+
+/// Kind of the unit cell of a [`Lattice`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatticeKind {
+    Chain,
+    Square,
+    Triangular,
+    Honeycomb,
+    Cubic,
+}
+
+/// Regular lattice of cells, with the sites of a cell numbered together and the cells
+/// numbered with x running fastest, so site = basis * (x + w * (y + h * z)) + sublattice.
+/// A periodic side of length 2 reaches the same cell both ways, so that bond is listed twice.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lattice {
+    pub kind: LatticeKind,
+    /// cells along each dimension
+    pub dims: Vec<usize>,
+    pub boundary: Boundary,
+    graph: Graph,
+}
+
+impl Lattice {
+    /// 1D chain of `len` sites, 2 neighbours
+    pub fn chain(len: usize, boundary: Boundary) -> Self {
+        Self::build(LatticeKind::Chain, vec![len], boundary)
+    }
+
+    /// `w` x `h` sites, 4 neighbours
+    pub fn square(w: usize, h: usize, boundary: Boundary) -> Self {
+        Self::build(LatticeKind::Square, vec![w, h], boundary)
+    }
+
+    /// `w` x `h` sites, 6 neighbours: the square ones plus the (1, -1) diagonal
+    pub fn triangular(w: usize, h: usize, boundary: Boundary) -> Self {
+        Self::build(LatticeKind::Triangular, vec![w, h], boundary)
+    }
+
+    /// `w` x `h` cells of 2 sites (A then B), 3 neighbours. A bonds with the B of
+    /// its cell, of the cell to the left and of the cell below (brick wall layout).
+    pub fn honeycomb(w: usize, h: usize, boundary: Boundary) -> Self {
+        Self::build(LatticeKind::Honeycomb, vec![w, h], boundary)
+    }
+
+    /// `w` x `h` x `d` sites, 6 neighbours
+    pub fn cubic(w: usize, h: usize, d: usize, boundary: Boundary) -> Self {
+        Self::build(LatticeKind::Cubic, vec![w, h, d], boundary)
+    }
+
+    /// Sites per cell
+    pub fn basis(&self) -> usize {
+        match self.kind {
+            LatticeKind::Honeycomb => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Number of sites
+    pub fn len(&self) -> usize {
+        self.graph.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graph.is_empty()
+    }
+
+    pub fn neighbours(&self, site: usize) -> &[usize] {
+        self.graph.neighbours(site)
+    }
+
+    /// Every bond once, as (a, b) with a < b
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.graph.edges()
+    }
+
+    /// Site of the `sublattice` of a cell, the cell having a coordinate per dimension
+    pub fn site(&self, cell: &[usize], sublattice: usize) -> usize {
+        let index = cell
+            .iter()
+            .zip(&self.dims)
+            .rev()
+            .fold(0, |index, (x, len)| index * len + x);
+        index * self.basis() + sublattice
+    }
+
+    /// Cell and sublattice of a site, the inverse of [`Lattice::site`]
+    pub fn cell(&self, site: usize) -> (Vec<usize>, usize) {
+        let mut index = site / self.basis();
+        let cell = self
+            .dims
+            .iter()
+            .map(|len| {
+                let x = index % len;
+                index /= len;
+                x
+            })
+            .collect();
+        (cell, site % self.basis())
+    }
+
+    fn build(kind: LatticeKind, dims: Vec<usize>, boundary: Boundary) -> Self {
+        // bonds (from sublattice, to sublattice, cell offset), each one from one end only
+        let bonds: &[(usize, usize, &[isize])] = match kind {
+            LatticeKind::Chain => &[(0, 0, &[1])],
+            LatticeKind::Square => &[(0, 0, &[1, 0]), (0, 0, &[0, 1])],
+            LatticeKind::Triangular => &[(0, 0, &[1, 0]), (0, 0, &[0, 1]), (0, 0, &[1, -1])],
+            LatticeKind::Honeycomb => &[(0, 1, &[0, 0]), (0, 1, &[-1, 0]), (0, 1, &[0, -1])],
+            LatticeKind::Cubic => &[(0, 0, &[1, 0, 0]), (0, 0, &[0, 1, 0]), (0, 0, &[0, 0, 1])],
+        };
+        let mut lattice = Self {
+            kind,
+            dims,
+            boundary,
+            graph: Graph::default(),
+        };
+
+        let cells: usize = lattice.dims.iter().product();
+        let mut edges = Vec::new();
+        for index in 0..cells {
+            let (cell, _) = lattice.cell(index * lattice.basis());
+            for (from, to, offset) in bonds {
+                if let Some(other) = lattice.shift(&cell, offset) {
+                    edges.push((lattice.site(&cell, *from), lattice.site(&other, *to)));
+                }
+            }
+        }
+        lattice.graph = Graph::from_edges(cells * lattice.basis(), edges);
+        lattice
+    }
+
+    /// Cell at an offset of another, None if it falls out of an open lattice
+    fn shift(&self, cell: &[usize], offset: &[isize]) -> Option<Vec<usize>> {
+        cell.iter()
+            .zip(offset)
+            .zip(&self.dims)
+            .map(|((&x, &dx), &len)| {
+                let x = x as isize + dx;
+                match self.boundary {
+                    Boundary::Periodic => Some(x.rem_euclid(len as isize) as usize),
+                    Boundary::Open => (0..len as isize).contains(&x).then_some(x as usize),
+                }
+            })
+            .collect()
+    }
+}
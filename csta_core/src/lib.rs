@@ -1,3 +1,5 @@
+pub mod lattice;
+pub mod neighbours;
 pub mod vec2;
 pub mod vec3;
 pub mod vec4;
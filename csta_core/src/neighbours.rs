@@ -0,0 +1,308 @@
+//! Cell lists and Verlet neighbour lists of particles in a periodic box, in 2D and 3D.
+//! Particles are numbered like the slice of positions the lists are built from.
+//!
+//! A cell list splits the box in cells at least as wide as the cutoff, so the particles
+//! closer than it to a position are in the cell of the position or in the adjacent ones.
+//! A Verlet list keeps, for every particle, the others closer than the cutoff plus a skin,
+//! and is valid while no particle has moved more than half the skin since it was built.
+//! Both are updated one particle at a time, after each accepted move.
+//!
+//! The particles of `csta_models` keep a cell list, a Verlet list is standalone:
+//! whoever moves the particles keeps it updated and asks it for the neighbours.
+//!
+
+use crate::{vec2::Vec2f64, vec3::Vec3f64};
+
+/// A position in a box of 2 or 3 dimensions
+pub trait Point: Copy {
+    const DIM: usize;
+
+    /// Coordinate along an axis below DIM
+    fn coordinate(&self, axis: usize) -> f64;
+}
+
+impl Point for Vec2f64 {
+    const DIM: usize = 2;
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        [self.0, self.1][axis]
+    }
+}
+
+impl Point for Vec3f64 {
+    const DIM: usize = 3;
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        [self.0, self.1, self.2][axis]
+    }
+}
+
+/// Squared distance between the closest periodic images of two positions, in a box of sides `side`
+pub fn periodic_distance_squared<P: Point>(a: P, b: P, side: P) -> f64 {
+    (0..P::DIM)
+        .map(|axis| {
+            let (d, l) = (
+                a.coordinate(axis) - b.coordinate(axis),
+                side.coordinate(axis),
+            );
+            let d = d - l * (d / l).round();
+            d * d
+        })
+        .sum()
+}
+
+/// Particles sorted by the cell of the box they're in, see the [module docs](self)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellList<P> {
+    side: P,
+    cutoff: f64,
+    // cells along each axis, 1 for the axes past DIM
+    shape: [usize; 3],
+    cells: Vec<Vec<usize>>,
+    // the cell itself and the adjacent ones, each once, of every cell
+    adjacent: Vec<Vec<usize>>,
+    cell_of: Vec<usize>,
+}
+
+impl<P: Point> CellList<P> {
+    /// Cell list of a box of sides `side`, with cells at least `cutoff` wide.
+    /// Panics if the cutoff isn't positive.
+    pub fn new(side: P, cutoff: f64, positions: &[P]) -> Self {
+        assert!(cutoff > 0.0, "the cutoff of a cell list has to be positive");
+        let mut shape = [1; 3];
+        for (axis, cells) in shape.iter_mut().enumerate().take(P::DIM) {
+            *cells = ((side.coordinate(axis) / cutoff).floor() as usize).max(1);
+        }
+        let count = shape.iter().product();
+        let adjacent = (0..count)
+            .map(|cell| {
+                let coordinates = [
+                    cell % shape[0],
+                    cell / shape[0] % shape[1],
+                    cell / shape[0] / shape[1],
+                ];
+                let mut adjacent = Vec::new();
+                for offset in 0..27 {
+                    let shift = [offset % 3, offset / 3 % 3, offset / 9];
+                    let mut index = 0;
+                    for axis in (0..3).rev() {
+                        let c = (coordinates[axis] + shape[axis] + shift[axis] - 1) % shape[axis];
+                        index = index * shape[axis] + c;
+                    }
+                    adjacent.push(index);
+                }
+                // with less than 3 cells along an axis the same cell is adjacent twice
+                adjacent.sort_unstable();
+                adjacent.dedup();
+                adjacent
+            })
+            .collect();
+        let mut list = Self {
+            side,
+            cutoff,
+            shape,
+            cells: vec![Vec::new(); count],
+            adjacent,
+            cell_of: Vec::new(),
+        };
+        list.rebuild(positions);
+        list
+    }
+
+    /// Sorts every particle again, after the positions changed by other means than
+    /// [`move_particle`](Self::move_particle)
+    pub fn rebuild(&mut self, positions: &[P]) {
+        self.cells.iter_mut().for_each(Vec::clear);
+        self.cell_of.clear();
+        for (index, &position) in positions.iter().enumerate() {
+            let cell = self.cell(position);
+            self.cells[cell].push(index);
+            self.cell_of.push(cell);
+        }
+    }
+
+    /// Particles in the list
+    pub fn len(&self) -> usize {
+        self.cell_of.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cell_of.is_empty()
+    }
+
+    /// Min width of the cells
+    pub fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Cell of a position, wrapped into the box
+    pub fn cell(&self, position: P) -> usize {
+        let mut index = 0;
+        for axis in (0..P::DIM).rev() {
+            let l = self.side.coordinate(axis);
+            let x = position.coordinate(axis).rem_euclid(l);
+            let c = ((x / l * self.shape[axis] as f64) as usize).min(self.shape[axis] - 1);
+            index = index * self.shape[axis] + c;
+        }
+        index
+    }
+
+    /// Cell the particle is in
+    pub fn cell_of(&self, index: usize) -> usize {
+        self.cell_of[index]
+    }
+
+    pub fn particles_in(&self, cell: usize) -> &[usize] {
+        &self.cells[cell]
+    }
+
+    /// Particles in the cell of the position and the adjacent ones, every particle
+    /// closer than the cutoff is among them. Each one is given once.
+    pub fn candidates(&self, position: P) -> impl Iterator<Item = usize> + '_ {
+        self.adjacent[self.cell(position)]
+            .iter()
+            .flat_map(|&cell| self.cells[cell].iter().copied())
+    }
+
+    /// Moves a particle to another position, for instance after an accepted change
+    pub fn move_particle(&mut self, index: usize, to: P) {
+        let (from, to) = (self.cell_of[index], self.cell(to));
+        if from != to {
+            self.take_out(index);
+            self.cells[to].push(index);
+            self.cell_of[index] = to;
+        }
+    }
+
+    /// Adds a particle at the position, numbered after the others
+    pub fn insert(&mut self, position: P) -> usize {
+        let (index, cell) = (self.len(), self.cell(position));
+        self.cells[cell].push(index);
+        self.cell_of.push(cell);
+        index
+    }
+
+    /// Removes a particle like [`Vec::swap_remove`], the last particle takes its number
+    pub fn remove(&mut self, index: usize) {
+        self.take_out(index);
+        let last = self.len() - 1;
+        if index != last {
+            let cell = self.cell_of[last];
+            let slot = self.cells[cell].iter().position(|&i| i == last).unwrap();
+            self.cells[cell][slot] = index;
+        }
+        self.cell_of.swap_remove(index);
+    }
+
+    // drops the particle from the cell it's in
+    fn take_out(&mut self, index: usize) {
+        let cell = &mut self.cells[self.cell_of[index]];
+        let slot = cell.iter().position(|&i| i == index).unwrap();
+        cell.swap_remove(slot);
+    }
+}
+
+/// Neighbours of every particle within the cutoff plus a skin, see the [module docs](self)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerletList<P> {
+    side: P,
+    cutoff: f64,
+    skin: f64,
+    neighbours: Vec<Vec<usize>>,
+    // positions when the list was built
+    reference: Vec<P>,
+    rebuilds: usize,
+}
+
+impl<P: Point> VerletList<P> {
+    /// Verlet list of a box of sides `side`.
+    /// Panics if the cutoff isn't positive or the skin is negative.
+    pub fn new(side: P, cutoff: f64, skin: f64, positions: &[P]) -> Self {
+        assert!(
+            cutoff > 0.0,
+            "the cutoff of a verlet list has to be positive"
+        );
+        assert!(skin >= 0.0, "the skin of a verlet list can't be negative");
+        let mut list = Self {
+            side,
+            cutoff,
+            skin,
+            neighbours: Vec::new(),
+            reference: Vec::new(),
+            rebuilds: 0,
+        };
+        list.rebuild(positions);
+        list
+    }
+
+    /// Builds the list again from the positions, with a cell list
+    pub fn rebuild(&mut self, positions: &[P]) {
+        let reach = self.cutoff + self.skin;
+        let cells = CellList::new(self.side, reach, positions);
+        self.neighbours = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &a)| {
+                cells
+                    .candidates(a)
+                    .filter(|&j| {
+                        j != i
+                            && periodic_distance_squared(a, positions[j], self.side) < reach * reach
+                    })
+                    .collect()
+            })
+            .collect();
+        self.reference = positions.to_vec();
+        self.rebuilds += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbours.is_empty()
+    }
+
+    pub fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+
+    pub fn skin(&self) -> f64 {
+        self.skin
+    }
+
+    /// Times the list has been built
+    pub fn rebuilds(&self) -> usize {
+        self.rebuilds
+    }
+
+    /// Particles that may be closer than the cutoff to this one, while the list is valid
+    pub fn neighbours(&self, index: usize) -> &[usize] {
+        &self.neighbours[index]
+    }
+
+    /// Whether the neighbours of the particle are still right with it at `position`,
+    /// which holds while it's within half the skin of where the list was built
+    pub fn covers(&self, index: usize, position: P) -> bool {
+        let half = self.skin / 2.0;
+        periodic_distance_squared(self.reference[index], position, self.side) <= half * half
+    }
+
+    /// Keeps the list valid after the particle moved to `positions[index]`,
+    /// rebuilding it if the particle went too far. Returns whether it was rebuilt.
+    pub fn update(&mut self, index: usize, positions: &[P]) -> bool {
+        if self.covers(index, positions[index]) {
+            false
+        } else {
+            self.rebuild(positions);
+            true
+        }
+    }
+}
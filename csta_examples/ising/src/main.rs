@@ -1,4 +1,13 @@
-use csta::{Metropolis, MonteCarlo, State, csta_derive::Randomizable};
+use csta::{
+    Boundary, Lattice, Metropolis, MonteCarlo, State, analysis,
+    chains::Chains,
+    cluster::{Cluster, SpinLattice},
+    csta_derive::Randomizable,
+    observer::{Cadence, Observer, sink::Accumulator},
+    seed::SeedRng,
+    tempering::ParallelTempering,
+    thermalization::Geweke,
+};
 
 use crate::observables::Magnetization;
 
@@ -11,13 +20,29 @@ fn main() {
     // make 10 states
     mc.take(10).enumerate().for_each(|(i, ising)| {
         // init metropolis
-        let mut metropolis = Metropolis::with_state(ising, (i as f64 + 1.0) / 5.0, 2_000);
+        let mut metropolis = Metropolis::builder()
+            .state(ising)
+            .beta((i as f64 + 1.0) / 5.0)
+            .steps(2_000)
+            .build()
+            .expect("valid metropolis");
+
+        // burn-in until the energy trace looks stationary
+        let equilibrated_at = metropolis.thermalize(&Geweke::default(), 500, 20_000);
+        println!("equilibrated after {equilibrated_at:?} steps");
 
         // running 1 observer
         let magnetizations = metropolis.run_with::<Magnetization>();
 
+        // running 1 observer with a cadence chosen at runtime
+        metropolis.run_with_observer(&Magnetization.with_cadence(Cadence::logarithmic(10, 0)));
+
+        // streaming 1 observer into a running mean, without keeping the observations
+        let mut mean_magnetization = Accumulator::new();
+        metropolis.run_with_sink(&Magnetization, &mut mean_magnetization);
+
         // running 2 observers
-        metropolis.run_with_2::<Magnetization, Magnetization>();
+        metropolis.run_with_set::<(Magnetization, Magnetization)>();
 
         // running n observers
         metropolis.run_with_n(vec![
@@ -31,7 +56,59 @@ fn main() {
 
         // show first 1 observer results
         println!("{:?}", magnetizations);
+        println!(
+            "{} ± {}",
+            analysis::mean(&magnetizations),
+            analysis::error_of_mean(&magnetizations)
+        );
     });
+
+    // same ladder of betas, but neighbouring temperatures exchange states
+    let replicas = MonteCarlo::<Ising, _>::default()
+        .take(10)
+        .enumerate()
+        .map(|(i, ising)| {
+            Metropolis::builder()
+                .state(ising)
+                .beta((i as f64 + 1.0) / 5.0)
+                .steps(2_000)
+                .build()
+                .expect("valid settings")
+        })
+        .collect();
+    let mut tempering = ParallelTempering::new(replicas, 2_000, 10, rand::rng());
+    let magnetizations = tempering.run_with::<Magnetization>();
+    println!("{:?}", magnetizations.last());
+    println!("{:?}", tempering.swap_acceptance_rates());
+
+    // independent chains at the same beta, to check that they agree
+    let mut chains = Chains::from_samples(
+        MonteCarlo::<Ising, SeedRng>::streams(42),
+        4,
+        |(ising, rng)| {
+            Metropolis::builder()
+                .state(ising)
+                .beta(0.4)
+                .steps(2_000)
+                .rng(rng)
+                .build()
+                .expect("valid settings")
+        },
+    );
+    let magnetizations = chains.run_with::<Magnetization>();
+    println!("{:?}", analysis::convergence(&magnetizations));
+
+    // near the critical point, wolff cluster flips decorrelate much faster than single flips
+    let ising = MonteCarlo::<Ising, _>::default().next().expect("a state");
+    let mut wolff = Cluster::wolff(ising, IsingParams::default(), 0.11, 2_000, rand::rng())
+        .expect("a finite beta");
+    let magnetizations = wolff.run_with::<Magnetization>();
+    println!(
+        "{} ± {}, {} spins per flip",
+        analysis::mean(&magnetizations),
+        analysis::error_of_mean(&magnetizations),
+        wolff.mean_flipped()
+    );
 }
 
 #[derive(Randomizable, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -55,8 +132,10 @@ impl Default for IsingParams {
 pub struct Ising {
     #[csta(default = 10)]
     w: usize,
-    #[csta(range(5..10))]
+    #[csta(default = 8)]
     h: usize,
+    #[csta(default = Lattice::square(w, h, Boundary::Periodic))]
+    lattice: Lattice,
     #[csta(len(w * h))]
     pub states: Vec<Spin>,
 }
@@ -74,6 +153,12 @@ impl Spin {
     }
 }
 
+impl Ising {
+    fn neighbours(&self, i: usize) -> impl Iterator<Item = &Spin> {
+        self.lattice.neighbours(i).iter().map(|&j| &self.states[j])
+    }
+}
+
 impl State for Ising {
     type Change = usize;
     type Params = IsingParams;
@@ -93,12 +178,50 @@ impl State for Ising {
     fn energy(&self, params: &mut Self::Params) -> f64 {
         let mut energy = 0.0;
         for i in 0..self.w * self.h {
-            for j in [i + 1, i - 1, i + self.w, i - self.w] {
-                if let Some(other) = self.states.get(j) {
-                    energy -= params.j * self.states[i].mul(other);
-                }
+            for other in self.neighbours(i) {
+                energy -= params.j * self.states[i].mul(other);
             }
         }
         energy
     }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        // every bond is counted twice in energy, and flipping changes its sign
+        let spin = &self.states[*change];
+        let local = self
+            .neighbours(*change)
+            .map(|other| spin.mul(other))
+            .sum::<f64>();
+        Some(4.0 * params.j * local)
+    }
+}
+
+impl SpinLattice for Ising {
+    type Spin = Spin;
+
+    fn sites(&self) -> usize {
+        self.states.len()
+    }
+
+    fn neighbours(&self, site: usize) -> impl Iterator<Item = usize> {
+        self.lattice.neighbours(site).iter().copied()
+    }
+
+    fn spin(&self, site: usize) -> Self::Spin {
+        self.states[site]
+    }
+
+    fn set_spin(&mut self, site: usize, spin: Self::Spin) {
+        self.states[site] = spin;
+    }
+
+    fn bond_energy(&self, _a: usize, _b: usize, params: &Self::Params) -> f64 {
+        // same as delta_energy, every bond is counted twice
+        4.0 * params.j
+    }
+
+    fn flipped(&self, mut spin: Self::Spin, _rng: &mut impl rand::Rng) -> Self::Spin {
+        spin.flip();
+        spin
+    }
 }
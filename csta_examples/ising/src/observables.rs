@@ -1,21 +1,23 @@
-use csta::observer::Observer;
+use csta::observer::{Cadence, Observer};
 
 use crate::{Ising, Spin};
 
+#[derive(Default)]
 pub struct Magnetization;
 
 impl Observer<Ising> for Magnetization {
     type Observation = f64;
 
-    fn after() -> usize {
-        0 // will measure magnetization from the beggining
+    fn cadence(&self) -> Cadence {
+        // will measure every 10 steps, from the beggining
+        Cadence::every(10, 0)
     }
 
-    fn every() -> usize {
-        10 // will measure every 10 steps
-    }
-
-    fn measure(state: &Ising, _params: &<Ising as csta::State>::Params) -> Self::Observation {
+    fn measure(
+        &self,
+        state: &Ising,
+        _params: &<Ising as csta::State>::Params,
+    ) -> Self::Observation {
         state
             .states
             .iter()
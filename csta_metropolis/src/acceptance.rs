@@ -0,0 +1,65 @@
+//! Rules deciding whether a proposed change is accepted
+//!
+//! All of them (but greedy) satisfy detailed balance, they differ on the dynamics.
+//! An infinite increase of energy is never accepted, not even at beta 0,
+//! and a NaN energy change is always rejected.
+
+/// Gives the probability of accepting a change, from the change of energy,
+/// beta and the Hastings correction ln q(x|x') - ln q(x'|x).
+pub trait AcceptanceRule {
+    fn probability(&self, delta_energy: f64, beta: f64, ln_q_ratio: f64) -> f64;
+}
+
+/// ln(exp(-beta dE) q(x|x') / q(x'|x)), where an infinite dE wins over a zero beta
+fn log_ratio(delta_energy: f64, beta: f64, ln_q_ratio: f64) -> f64 {
+    if delta_energy.is_infinite() {
+        -delta_energy
+    } else {
+        -beta * delta_energy + ln_q_ratio
+    }
+}
+
+/// min(1, exp(-beta dE) q(x|x') / q(x'|x)), the default
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MetropolisRule;
+
+impl AcceptanceRule for MetropolisRule {
+    fn probability(&self, delta_energy: f64, beta: f64, ln_q_ratio: f64) -> f64 {
+        let log_ratio = log_ratio(delta_energy, beta, ln_q_ratio);
+        if log_ratio.is_nan() {
+            0.0
+        } else {
+            log_ratio.min(0.0).exp()
+        }
+    }
+}
+
+/// Heat bath, r / (1 + r) with r = exp(-beta dE) q(x|x') / q(x'|x).
+/// Accepts less than metropolis, but flips are smooth in the energy.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Glauber;
+
+/// Barker's rule is the same as Glauber's
+pub type Barker = Glauber;
+
+impl AcceptanceRule for Glauber {
+    fn probability(&self, delta_energy: f64, beta: f64, ln_q_ratio: f64) -> f64 {
+        let log_ratio = log_ratio(delta_energy, beta, ln_q_ratio);
+        if log_ratio.is_nan() {
+            0.0
+        } else {
+            1.0 / (1.0 + (-log_ratio).exp())
+        }
+    }
+}
+
+/// Zero temperature: accepts only changes that don't increase the energy, beta is ignored.
+/// Doesn't sample any distribution, it's a quench to the closest local minimum.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Greedy;
+
+impl AcceptanceRule for Greedy {
+    fn probability(&self, delta_energy: f64, _beta: f64, _ln_q_ratio: f64) -> f64 {
+        if delta_energy <= 0.0 { 1.0 } else { 0.0 }
+    }
+}
@@ -0,0 +1,261 @@
+//! Error analysis of the series of observations given by the `run_with*` methods
+//!
+//! Observations of a markov chain are correlated, so the naive error of the mean
+//! underestimates the real one. These functions take that into account.
+//! Those that can't be computed from too few observations or blocks return None.
+
+use rand::Rng;
+
+pub fn mean(data: &[f64]) -> f64 {
+    data.iter().sum::<f64>() / data.len() as f64
+}
+
+/// Sample variance, NaN with less than 2 observations
+pub fn variance(data: &[f64]) -> f64 {
+    if data.len() < 2 {
+        return f64::NAN;
+    }
+    let mean = mean(data);
+    data.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (data.len() - 1) as f64
+}
+
+/// Normalized autocorrelation function at lag `t`, 0 past the end of the series.
+/// None for a series that doesn't fluctuate (constant or empty), it can't be normalized.
+pub fn autocorrelation(data: &[f64], t: usize) -> Option<f64> {
+    let n = data.len();
+    let mean = mean(data);
+    let c0 = data.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n as f64;
+    if c0.is_nan() || c0 == 0.0 {
+        return None;
+    }
+    if t >= n {
+        return Some(0.0);
+    }
+    let ct = (0..n - t)
+        .map(|i| (data[i] - mean) * (data[i + t] - mean))
+        .sum::<f64>()
+        / (n - t) as f64;
+    Some(ct / c0)
+}
+
+/// Integrated autocorrelation time, tau = 1/2 + sum of the autocorrelations,
+/// summed up to the first window W with W >= 6 tau (Sokal's automatic windowing).
+/// An uncorrelated series gives 1/2, and so does a constant one.
+pub fn integrated_autocorrelation_time(data: &[f64]) -> f64 {
+    let mut tau = 0.5;
+    for t in 1..data.len() {
+        let Some(rho) = autocorrelation(data, t) else {
+            break;
+        };
+        tau += rho;
+        if t as f64 >= 6.0 * tau {
+            break;
+        }
+    }
+    tau
+}
+
+/// Number of independent observations the series is worth, n / (2 tau)
+pub fn effective_sample_size(data: &[f64]) -> f64 {
+    data.len() as f64 / (2.0 * integrated_autocorrelation_time(data))
+}
+
+/// Error of the mean taking the autocorrelation into account, sqrt(2 tau var / n)
+pub fn error_of_mean(data: &[f64]) -> f64 {
+    (variance(data) / effective_sample_size(data)).sqrt()
+}
+
+/// Means of consecutive blocks of `size` observations, the last incomplete block is dropped.
+/// There are no blocks of size 0.
+pub fn blocks(data: &[f64], size: usize) -> Vec<f64> {
+    if size == 0 {
+        return Vec::new();
+    }
+    data.chunks_exact(size).map(mean).collect()
+}
+
+/// Error of the mean from the means of blocks of `size` observations, None with less than 2 blocks.
+/// Once the blocks are longer than the autocorrelation time they are independent
+/// and this error stops growing with the size.
+pub fn blocking_error(data: &[f64], size: usize) -> Option<f64> {
+    let blocks = blocks(data, size);
+    if blocks.len() < 2 {
+        return None;
+    }
+    Some((variance(&blocks) / blocks.len() as f64).sqrt())
+}
+
+/// Blocking error for block sizes 1, 2, 4, ... while there are at least `min_blocks` blocks,
+/// as (size, error). The plateau of the errors is the error of the mean.
+pub fn blocking_analysis(data: &[f64], min_blocks: usize) -> Vec<(usize, f64)> {
+    let min_blocks = min_blocks.max(2);
+    let mut size = 1;
+    let mut errors = Vec::new();
+    while data.len() / size >= min_blocks {
+        if let Some(error) = blocking_error(data, size) {
+            errors.push((size, error));
+        }
+        size *= 2;
+    }
+    errors
+}
+
+/// Jackknife estimate and error of `estimator` over blocks of `size` observations,
+/// use blocks longer than the autocorrelation time.
+/// Works for non linear estimators, like the susceptibility from magnetizations.
+/// None with less than 2 blocks, as one has to be left out.
+pub fn jackknife(
+    data: &[f64],
+    size: usize,
+    estimator: impl Fn(&[f64]) -> f64,
+) -> Option<(f64, f64)> {
+    if size == 0 || data.len() / size < 2 {
+        return None;
+    }
+    let blocks: Vec<&[f64]> = data.chunks_exact(size).collect();
+    let n = blocks.len();
+    let estimates: Vec<f64> = (0..n)
+        .map(|leave| {
+            let sample: Vec<f64> = blocks
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != leave)
+                .flat_map(|(_, block)| block.iter().copied())
+                .collect();
+            estimator(&sample)
+        })
+        .collect();
+    let full = estimator(&data[..n * size]);
+    let jack_mean = mean(&estimates);
+    let estimate = n as f64 * full - (n - 1) as f64 * jack_mean;
+    let error = ((n - 1) as f64 / n as f64
+        * estimates
+            .iter()
+            .map(|e| (e - jack_mean) * (e - jack_mean))
+            .sum::<f64>())
+    .sqrt();
+    Some((estimate, error))
+}
+
+/// Bootstrap estimate and error of `estimator`, resampling `resamples` times
+/// blocks of `size` observations with replacement.
+/// None without a block or with less than 2 resamples, which give no error.
+pub fn bootstrap(
+    data: &[f64],
+    size: usize,
+    resamples: usize,
+    rng: &mut impl Rng,
+    estimator: impl Fn(&[f64]) -> f64,
+) -> Option<(f64, f64)> {
+    if size == 0 || data.len() < size || resamples < 2 {
+        return None;
+    }
+    let blocks: Vec<&[f64]> = data.chunks_exact(size).collect();
+    let n = blocks.len();
+    let mut sample = Vec::with_capacity(n * size);
+    let estimates: Vec<f64> = (0..resamples)
+        .map(|_| {
+            sample.clear();
+            for _ in 0..n {
+                sample.extend_from_slice(blocks[rng.random_range(0..n)]);
+            }
+            estimator(&sample)
+        })
+        .collect();
+    Some((mean(&estimates), variance(&estimates).sqrt()))
+}
+
+/// Split R-hat and effective sample size of one observation measured over several chains
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Convergence {
+    pub r_hat: f64,
+    pub ess: f64,
+}
+
+/// Split R-hat and multi-chain effective sample size, see [`split_r_hat`] and [`multi_chain_ess`]
+pub fn convergence(chains: &[impl AsRef<[f64]>]) -> Convergence {
+    Convergence {
+        r_hat: split_r_hat(chains),
+        ess: multi_chain_ess(chains),
+    }
+}
+
+/// Convergence of one of the observers of a set, from the tuples of observations of each
+/// chain given by [`Chains::run_with_observers`](crate::chains::Chains::run_with_observers),
+/// with `pick` taking its series out of a tuple, like `|(energies, _)| energies`
+pub fn convergence_of<T>(runs: &[T], pick: impl Fn(&T) -> &[f64]) -> Convergence {
+    let chains: Vec<&[f64]> = runs.iter().map(pick).collect();
+    convergence(&chains)
+}
+
+/// Each chain cut in halves, with the same length, the middle observation of odd chains dropped
+fn split_chains(chains: &[impl AsRef<[f64]>]) -> Vec<&[f64]> {
+    let n = chains.iter().map(|c| c.as_ref().len()).min().unwrap_or(0) / 2;
+    chains
+        .iter()
+        .flat_map(|c| {
+            let c = c.as_ref();
+            [&c[..n], &c[c.len() - n..]]
+        })
+        .collect()
+}
+
+/// Within chain variance W and the pooled estimate var+ of the split chains
+fn pooled_variances(split: &[&[f64]]) -> (f64, f64) {
+    let n = split[0].len() as f64;
+    let means: Vec<f64> = split.iter().map(|c| mean(c)).collect();
+    let b = n * variance(&means);
+    let w = mean(&split.iter().map(|c| variance(c)).collect::<Vec<f64>>());
+    (w, (n - 1.0) / n * w + b / n)
+}
+
+/// Gelman-Rubin potential scale reduction over the split chains,
+/// close to 1 when the chains agree with each other (1.01 is a common threshold).
+/// Needs at least 2 observations in each half of every chain.
+pub fn split_r_hat(chains: &[impl AsRef<[f64]>]) -> f64 {
+    let split = split_chains(chains);
+    if split.is_empty() || split[0].len() < 2 {
+        return f64::NAN;
+    }
+    let (w, var_plus) = pooled_variances(&split);
+    (var_plus / w).sqrt()
+}
+
+/// Effective sample size of all the chains together, with the autocorrelations
+/// combined between the split chains and summed with Geyer's initial positive sequence.
+pub fn multi_chain_ess(chains: &[impl AsRef<[f64]>]) -> f64 {
+    let split = split_chains(chains);
+    if split.is_empty() || split[0].len() < 4 {
+        return f64::NAN;
+    }
+    let n = split[0].len();
+    let m = split.len();
+    let (w, var_plus) = pooled_variances(&split);
+    let means: Vec<f64> = split.iter().map(|c| mean(c)).collect();
+    let rho = |t: usize| {
+        let autocovariance = split
+            .iter()
+            .zip(means.iter())
+            .map(|(c, mean)| {
+                (0..n - t)
+                    .map(|i| (c[i] - mean) * (c[i + t] - mean))
+                    .sum::<f64>()
+                    / n as f64
+            })
+            .sum::<f64>()
+            / m as f64;
+        1.0 - (w - autocovariance) / var_plus
+    };
+
+    let mut tau = -1.0;
+    let mut t = 0;
+    while t + 1 < n {
+        let pair = rho(t) + rho(t + 1);
+        if pair < 0.0 {
+            break;
+        }
+        tau += 2.0 * pair;
+        t += 2;
+    }
+    (m * n) as f64 / tau
+}
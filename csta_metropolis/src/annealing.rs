@@ -0,0 +1,123 @@
+//! Simulated annealing, metropolis with a beta that changes with the step
+
+use rand::Rng;
+
+use crate::{Metropolis, State, acceptance::AcceptanceRule};
+
+/// Maps the step index of a run to the beta used at that step
+pub trait Schedule {
+    fn beta(&self, step: usize) -> f64;
+}
+
+/// Any `Fn(step) -> beta` is a schedule
+impl<F> Schedule for F
+where
+    F: Fn(usize) -> f64,
+{
+    fn beta(&self, step: usize) -> f64 {
+        self(step)
+    }
+}
+
+/// Goes from `from` to `to` in `steps` steps, then stays at `to`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Linear {
+    pub from: f64,
+    pub to: f64,
+    pub steps: usize,
+}
+
+impl Schedule for Linear {
+    fn beta(&self, step: usize) -> f64 {
+        if step >= self.steps {
+            return self.to;
+        }
+        self.from + (self.to - self.from) * step as f64 / self.steps as f64
+    }
+}
+
+/// beta = from * ratio^step, a ratio over 1 cools down the system
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometric {
+    pub from: f64,
+    pub ratio: f64,
+}
+
+impl Schedule for Geometric {
+    fn beta(&self, step: usize) -> f64 {
+        self.from * self.ratio.powf(step as f64)
+    }
+}
+
+/// beta = ln(1 + step) / c, the classic schedule that guarantees (slowly) reaching
+/// the ground state for a large enough c
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Logarithmic {
+    pub c: f64,
+}
+
+impl Schedule for Logarithmic {
+    fn beta(&self, step: usize) -> f64 {
+        (1.0 + step as f64).ln() / self.c
+    }
+}
+
+/// Chains schedules, each one running for a number of steps.
+/// The steps passed to each piece are relative to the start of that piece,
+/// the last piece is used for every step after the end.
+pub struct Piecewise {
+    // never empty
+    pieces: Vec<(usize, Box<dyn Schedule>)>,
+}
+
+impl Piecewise {
+    /// Starts with a first piece, so there's always a beta
+    pub fn new(steps: usize, schedule: impl Schedule + 'static) -> Self {
+        Self {
+            pieces: vec![(steps, Box::new(schedule))],
+        }
+    }
+
+    pub fn then(mut self, steps: usize, schedule: impl Schedule + 'static) -> Self {
+        self.pieces.push((steps, Box::new(schedule)));
+        self
+    }
+}
+
+impl Schedule for Piecewise {
+    fn beta(&self, step: usize) -> f64 {
+        let mut start = 0;
+        let (last, pieces) = self.pieces.split_last().expect("pieces are never empty");
+        for (steps, schedule) in pieces {
+            if step < start + steps {
+                return schedule.beta(step - start);
+            }
+            start += steps;
+        }
+        last.1.beta(step - start)
+    }
+}
+
+impl<S, R, A> Metropolis<S, R, A>
+where
+    S: State + Clone,
+    R: Rng,
+    A: AcceptanceRule,
+{
+    /// Runs `steps` steps setting beta from the schedule before each one.
+    /// Returns the lowest energy state seen and its energy.
+    /// beta is left at the last value of the schedule.
+    pub fn run_annealing(&mut self, schedule: &impl Schedule) -> (S, f64) {
+        let mut best_state = self.state.clone();
+        let mut best_energy = self.energy;
+        for i in 0..self.steps {
+            self.beta = schedule.beta(i);
+            self.step();
+            if self.energy < best_energy {
+                best_energy = self.energy;
+                best_state = self.state.clone();
+            }
+        }
+        (best_state, best_energy)
+    }
+}
@@ -0,0 +1,226 @@
+//! Builder for metropolis, so every knob doesn't need its own `with_*` constructor
+
+use std::fmt;
+
+use csta_montecarlo::{
+    Randomizable,
+    seed::{self, SeedRng},
+};
+use rand::{Rng, rngs::ThreadRng};
+
+use crate::{
+    Metropolis, State,
+    acceptance::{AcceptanceRule, MetropolisRule},
+    moves::MoveSet,
+    observer::{ObserveInto, ObserverSet},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    MissingState,
+    MissingSteps,
+    ZeroSteps,
+    NonFiniteBeta(f64),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingState => write!(f, "no state given, use state or sample_state"),
+            BuildError::MissingSteps => write!(f, "the number of steps was not set"),
+            BuildError::ZeroSteps => write!(f, "steps must be greater than 0"),
+            BuildError::NonFiniteBeta(beta) => write!(f, "beta must be finite, got {beta}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// A metropolis after its run, with the observations of each observer
+pub type Observed<S, R, A, O> = (Metropolis<S, R, A>, <O as ObserverSet<S>>::Observations);
+
+/// Settings of a metropolis, checked by [`build`](MetropolisBuilder::build),
+/// or by [`run`](MetropolisBuilder::run) once observers are given
+pub struct MetropolisBuilder<S: State, R: Rng, A = MetropolisRule, O = ()> {
+    state: Option<S>,
+    params: S::Params,
+    beta: f64,
+    steps: Option<usize>,
+    thermalization: usize,
+    moves: Option<MoveSet<S>>,
+    rng: R,
+    rule: A,
+    observers: O,
+}
+
+impl<S> Metropolis<S, ThreadRng>
+where
+    S: State,
+    S::Params: Default,
+{
+    /// Builder with default params, beta 1 and thread rng
+    pub fn builder() -> MetropolisBuilder<S, ThreadRng> {
+        MetropolisBuilder::with_params(S::Params::default())
+    }
+}
+
+impl<S: State> MetropolisBuilder<S, ThreadRng> {
+    /// For params without a default
+    pub fn with_params(params: S::Params) -> Self {
+        Self {
+            state: None,
+            params,
+            beta: 1.0,
+            steps: None,
+            thermalization: 0,
+            moves: None,
+            rng: rand::rng(),
+            rule: MetropolisRule,
+            observers: (),
+        }
+    }
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule, O> MetropolisBuilder<S, R, A, O> {
+    pub fn state(mut self, state: S) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Samples the state using the rng set up to this point
+    pub fn sample_state(mut self) -> Self
+    where
+        S: Randomizable,
+    {
+        self.state = Some(S::sample(&mut self.rng));
+        self
+    }
+
+    pub fn params(mut self, params: S::Params) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn beta(mut self, beta: f64) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    pub fn steps(mut self, steps: usize) -> Self {
+        self.steps = Some(steps);
+        self
+    }
+
+    /// Steps ran when building, they don't count towards the accepted or invalid moves
+    pub fn thermalization(mut self, thermalization: usize) -> Self {
+        self.thermalization = thermalization;
+        self
+    }
+
+    /// Proposes the changes with the moves of the set instead of propose_change
+    pub fn moves(mut self, moves: MoveSet<S>) -> Self {
+        self.moves = Some(moves);
+        self
+    }
+
+    pub fn rng<R2: Rng>(self, rng: R2) -> MetropolisBuilder<S, R2, A, O> {
+        MetropolisBuilder {
+            state: self.state,
+            params: self.params,
+            beta: self.beta,
+            steps: self.steps,
+            thermalization: self.thermalization,
+            moves: self.moves,
+            rng,
+            rule: self.rule,
+            observers: self.observers,
+        }
+    }
+
+    /// Rng of [`seed::seeded`], stream 0 of the seed
+    pub fn seed(self, seed: u64) -> MetropolisBuilder<S, SeedRng, A, O> {
+        self.rng(seed::seeded(seed))
+    }
+
+    /// Observers measured by [`run`](MetropolisBuilder::run), a tuple like `(Energy, Magnetization)`
+    pub fn observers<Set>(self, observers: Set) -> MetropolisBuilder<S, R, A, Set>
+    where
+        Set: ObserverSet<S>,
+    {
+        MetropolisBuilder {
+            state: self.state,
+            params: self.params,
+            beta: self.beta,
+            steps: self.steps,
+            thermalization: self.thermalization,
+            moves: self.moves,
+            rng: self.rng,
+            rule: self.rule,
+            observers,
+        }
+    }
+
+    /// Accepts the changes with another rule than [`MetropolisRule`], thermalization included
+    pub fn rule<B: AcceptanceRule>(self, rule: B) -> MetropolisBuilder<S, R, B, O> {
+        MetropolisBuilder {
+            state: self.state,
+            params: self.params,
+            beta: self.beta,
+            steps: self.steps,
+            thermalization: self.thermalization,
+            moves: self.moves,
+            rng: self.rng,
+            rule,
+            observers: self.observers,
+        }
+    }
+
+    // the metropolis, thermalized, and the observers
+    fn checked(self) -> Result<(Metropolis<S, R, A>, O), BuildError> {
+        let state = self.state.ok_or(BuildError::MissingState)?;
+        let steps = self.steps.ok_or(BuildError::MissingSteps)?;
+        if steps == 0 {
+            return Err(BuildError::ZeroSteps);
+        }
+        if !self.beta.is_finite() {
+            return Err(BuildError::NonFiniteBeta(self.beta));
+        }
+
+        let mut metropolis =
+            Metropolis::new(state, self.params, self.beta, steps, self.rng).with_rule(self.rule);
+        if let Some(moves) = self.moves {
+            metropolis = metropolis.with_moves(moves);
+        }
+        for _ in 0..self.thermalization {
+            metropolis.step();
+        }
+        metropolis.accepted_moves = 0;
+        metropolis.invalid_moves = 0;
+        if let Some(moves) = metropolis.move_set_mut() {
+            moves.reset_counts();
+        }
+        Ok((metropolis, self.observers))
+    }
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule> MetropolisBuilder<S, R, A> {
+    pub fn build(self) -> Result<Metropolis<S, R, A>, BuildError> {
+        self.checked().map(|(metropolis, _)| metropolis)
+    }
+}
+
+impl<S, R, A, O> MetropolisBuilder<S, R, A, O>
+where
+    S: State,
+    R: Rng,
+    A: AcceptanceRule,
+    O: ObserverSet<S> + ObserveInto<S, <O as ObserverSet<S>>::Observations>,
+{
+    /// Builds the metropolis and runs it with the observers,
+    /// returning it with their observations
+    pub fn run(self) -> Result<Observed<S, R, A, O>, BuildError> {
+        let (mut metropolis, observers) = self.checked()?;
+        let observations = metropolis.run_with_observers(&observers);
+        Ok((metropolis, observations))
+    }
+}
@@ -0,0 +1,111 @@
+//! Independent chains of the same system, to check that they agree
+//! with [`crate::analysis::convergence`], or [`crate::analysis::convergence_of`]
+//! for each observer of a set
+
+use rand::Rng;
+
+use crate::{
+    Metropolis, State,
+    acceptance::{AcceptanceRule, MetropolisRule},
+    observer::{ObserveInto, Observer, ObserverSet},
+};
+
+pub struct Chains<S: State, R: Rng, A: AcceptanceRule = MetropolisRule> {
+    pub chains: Vec<Metropolis<S, R, A>>,
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule> Chains<S, R, A> {
+    pub fn new(chains: Vec<Metropolis<S, R, A>>) -> Self {
+        Self { chains }
+    }
+
+    /// Takes `n` samples, from a montecarlo for example, and builds a chain from each one
+    pub fn from_samples<T>(
+        samples: impl IntoIterator<Item = T>,
+        n: usize,
+        build: impl FnMut(T) -> Metropolis<S, R, A>,
+    ) -> Self {
+        Self::new(samples.into_iter().take(n).map(build).collect())
+    }
+
+    pub fn run_empty(&mut self) {
+        self.chains.iter_mut().for_each(|chain| chain.run_empty());
+    }
+
+    /// Returns the observations of each chain
+    pub fn run_with<O: Observer<S> + Default>(&mut self) -> Vec<Vec<O::Observation>> {
+        self.run_with_observer(&O::default())
+    }
+
+    pub fn run_with_observer<O: Observer<S>>(&mut self, observer: &O) -> Vec<Vec<O::Observation>> {
+        self.chains
+            .iter_mut()
+            .map(|chain| chain.run_with_observer(observer))
+            .collect()
+    }
+
+    /// Returns the tuple of observations of each chain
+    pub fn run_with_observers<Set>(&mut self, observers: &Set) -> Vec<Set::Observations>
+    where
+        Set: ObserverSet<S> + ObserveInto<S, <Set as ObserverSet<S>>::Observations>,
+    {
+        self.chains
+            .iter_mut()
+            .map(|chain| chain.run_with_observers(observers))
+            .collect()
+    }
+
+    pub fn accepted_rates(&self) -> Vec<f64> {
+        self.chains.iter().map(|c| c.accepted_rate()).collect()
+    }
+}
+
+/// Same runs as the serial ones, with each chain in a thread of rayon's pool.
+/// Every chain keeps its own rng, so the results are the same as the serial run.
+#[cfg(feature = "parallel")]
+impl<S, R, A> Chains<S, R, A>
+where
+    S: State + Send,
+    S::Params: Send,
+    R: Rng + Send,
+    A: AcceptanceRule + Send,
+{
+    pub fn par_run_empty(&mut self) {
+        use rayon::prelude::*;
+        self.chains
+            .par_iter_mut()
+            .for_each(|chain| chain.run_empty());
+    }
+
+    pub fn par_run_with<O>(&mut self) -> Vec<Vec<O::Observation>>
+    where
+        O: Observer<S> + Default + Sync,
+        O::Observation: Send,
+    {
+        self.par_run_with_observer(&O::default())
+    }
+
+    pub fn par_run_with_observer<O>(&mut self, observer: &O) -> Vec<Vec<O::Observation>>
+    where
+        O: Observer<S> + Sync,
+        O::Observation: Send,
+    {
+        use rayon::prelude::*;
+        self.chains
+            .par_iter_mut()
+            .map(|chain| chain.run_with_observer(observer))
+            .collect()
+    }
+
+    pub fn par_run_with_observers<Set>(&mut self, observers: &Set) -> Vec<Set::Observations>
+    where
+        Set: ObserverSet<S> + ObserveInto<S, <Set as ObserverSet<S>>::Observations> + Sync,
+        Set::Observations: Send,
+    {
+        use rayon::prelude::*;
+        self.chains
+            .par_iter_mut()
+            .map(|chain| chain.run_with_observers(observers))
+            .collect()
+    }
+}
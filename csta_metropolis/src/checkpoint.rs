@@ -0,0 +1,143 @@
+//! Checkpoints of a metropolis run, to resume it after a crash
+//!
+//! A checkpoint holds everything the run depends on (state, params, beta, step,
+//! accepted and invalid moves, rng and the observations so far), so resuming from one gives
+//! exactly the same observations as the uninterrupted run.
+//! The format is left to the user, anything serde can write works as long as
+//! floats round trip exactly (for serde_json, its `float_roundtrip` feature).
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{Metropolis, State, acceptance::AcceptanceRule, moves::MoveSet, observer::Observer};
+
+/// Checkpoint borrowed from a running metropolis, meant to be serialized
+#[derive(Serialize)]
+#[serde(bound(serialize = "S: Serialize, S::Params: Serialize, R: Serialize, Obs: Serialize"))]
+pub struct Checkpoint<'a, S: State, R, Obs> {
+    pub state: &'a S,
+    pub params: &'a S::Params,
+    pub beta: f64,
+    pub steps: usize,
+    /// next step to run
+    pub step: usize,
+    pub accepted_moves: usize,
+    pub invalid_moves: usize,
+    pub energy: f64,
+    pub rng: &'a R,
+    pub observations: &'a [Obs],
+}
+
+/// A [`Checkpoint`] read back, to resume with [`Metropolis::resume_with`]
+#[derive(Deserialize)]
+#[serde(bound(
+    deserialize = "S: Deserialize<'de>, S::Params: Deserialize<'de>, R: Deserialize<'de>, Obs: Deserialize<'de>"
+))]
+pub struct LoadedCheckpoint<S: State, R, Obs> {
+    pub state: S,
+    pub params: S::Params,
+    pub beta: f64,
+    pub steps: usize,
+    pub step: usize,
+    pub accepted_moves: usize,
+    /// missing in checkpoints from before it was counted
+    #[serde(default)]
+    pub invalid_moves: usize,
+    pub energy: f64,
+    pub rng: R,
+    pub observations: Vec<Obs>,
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule> Metropolis<S, R, A> {
+    /// Same as run_with_observer, but every `every` steps `save` gets a checkpoint of the run.
+    /// An error from `save` stops the run.
+    pub fn run_with_checkpoints<O, F, E>(
+        &mut self,
+        observer: &O,
+        every: usize,
+        save: F,
+    ) -> Result<Vec<O::Observation>, E>
+    where
+        O: Observer<S>,
+        F: FnMut(&Checkpoint<S, R, O::Observation>) -> Result<(), E>,
+    {
+        self.run_from(observer, 0, Vec::new(), every, save)
+    }
+
+    fn run_from<O, F, E>(
+        &mut self,
+        observer: &O,
+        start: usize,
+        mut measures: Vec<O::Observation>,
+        every: usize,
+        mut save: F,
+    ) -> Result<Vec<O::Observation>, E>
+    where
+        O: Observer<S>,
+        F: FnMut(&Checkpoint<S, R, O::Observation>) -> Result<(), E>,
+    {
+        let cadence = observer.cadence();
+        for i in start..self.steps {
+            if cadence.measures(i) {
+                measures.push(observer.measure_with_energy(&self.state, &self.params, self.energy));
+            }
+            self.step();
+            if every > 0 && (i + 1).is_multiple_of(every) {
+                save(&Checkpoint {
+                    state: &self.state,
+                    params: &self.params,
+                    beta: self.beta,
+                    steps: self.steps,
+                    step: i + 1,
+                    accepted_moves: self.accepted_moves,
+                    invalid_moves: self.invalid_moves,
+                    energy: self.energy,
+                    rng: &self.rng,
+                    observations: &measures,
+                })?;
+            }
+        }
+        Ok(measures)
+    }
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule> Metropolis<S, R, A> {
+    /// Continues the run of a checkpoint, returning all of its observations,
+    /// the ones from before the checkpoint included.
+    /// The rule and the move set can't be saved, they have to be the ones of the
+    /// interrupted run (None if its changes came from propose_change).
+    /// The counts of the moves go on from the ones of `moves`.
+    pub fn resume_with<O, F, E>(
+        checkpoint: LoadedCheckpoint<S, R, O::Observation>,
+        rule: A,
+        moves: Option<MoveSet<S>>,
+        observer: &O,
+        every: usize,
+        save: F,
+    ) -> Result<(Self, Vec<O::Observation>), E>
+    where
+        O: Observer<S>,
+        F: FnMut(&Checkpoint<S, R, O::Observation>) -> Result<(), E>,
+    {
+        let mut metropolis = Self {
+            state: checkpoint.state,
+            params: checkpoint.params,
+            beta: checkpoint.beta,
+            steps: checkpoint.steps,
+            accepted_moves: checkpoint.accepted_moves,
+            invalid_moves: checkpoint.invalid_moves,
+            rng: checkpoint.rng,
+            rule,
+            moves,
+            energy: checkpoint.energy,
+        };
+        let measures = metropolis.run_from(
+            observer,
+            checkpoint.step,
+            checkpoint.observations,
+            every,
+            save,
+        )?;
+        Ok((metropolis, measures))
+    }
+}
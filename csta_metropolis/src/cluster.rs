@@ -0,0 +1,281 @@
+//! Cluster updates (Wolff and Swendsen-Wang) for lattices of discrete spins
+//!
+//! Instead of a single spin, whole clusters of aligned spins are flipped at once,
+//! grown by joining aligned neighbours with probability 1 - exp(-beta dE_bond),
+//! so near the critical point the decorrelation time stays small.
+//! Only valid for ferromagnetic couplings, and the energy has to be a sum of bonds,
+//! which [`Cluster::new`] checks through [`SpinLattice::check_params`].
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::{
+    State,
+    observer::{Observer, sink::Sink},
+};
+
+/// A graph of discrete spins, the sites being 0..sites
+pub trait SpinLattice: State {
+    type Spin: Copy + PartialEq;
+
+    fn sites(&self) -> usize;
+
+    /// Neighbours of a site, if j is a neighbour of i then i has to be a neighbour of j
+    fn neighbours(&self, site: usize) -> impl Iterator<Item = usize>;
+
+    fn spin(&self, site: usize) -> Self::Spin;
+
+    fn set_spin(&mut self, site: usize, spin: Self::Spin);
+
+    /// Energy that misaligning the aligned neighbours a and b costs, not negative
+    /// (ferromagnetic). 2J for an ising bond -J s_a s_b.
+    fn bond_energy(&self, a: usize, b: usize, params: &Self::Params) -> f64;
+
+    /// Value a cluster of `spin`s is flipped to, the opposite spin for ising,
+    /// any other value (at random, with a symmetric choice) for more than two values
+    fn flipped(&self, spin: Self::Spin, rng: &mut impl Rng) -> Self::Spin;
+
+    /// Whether cluster updates sample the params, they don't with terms
+    /// that aren't bonds, like a field. Any params are fine by default.
+    fn check_params(&self, _params: &Self::Params) -> Result<(), ClusterError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClusterError {
+    /// refused by [`SpinLattice::check_params`], with the reason
+    UnsupportedParams(String),
+    NonFiniteBeta(f64),
+}
+
+impl fmt::Display for ClusterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClusterError::UnsupportedParams(reason) => {
+                write!(f, "cluster updates can't sample these params, {reason}")
+            }
+            ClusterError::NonFiniteBeta(beta) => write!(f, "beta must be finite, got {beta}"),
+        }
+    }
+}
+
+impl std::error::Error for ClusterError {}
+
+/// Which cluster update a [`Cluster`] step does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClusterUpdate {
+    /// grows a single cluster from a random site and flips it
+    #[default]
+    Wolff,
+    /// splits the whole lattice in clusters and flips each one with probability 1/2
+    SwendsenWang,
+}
+
+/// Runs cluster updates on a lattice, in place of [`crate::Metropolis`]
+pub struct Cluster<S: SpinLattice, R: Rng> {
+    pub state: S,
+    pub params: S::Params,
+    pub beta: f64,
+    pub steps: usize,
+    pub update: ClusterUpdate,
+    /// spins flipped in all the steps so far
+    pub flipped_spins: usize,
+    pub rng: R,
+}
+
+impl<S: SpinLattice, R: Rng> Cluster<S, R> {
+    /// Fails if beta isn't finite or the state refuses the params,
+    /// see [`SpinLattice::check_params`]
+    pub fn new(
+        state: S,
+        params: S::Params,
+        beta: f64,
+        steps: usize,
+        update: ClusterUpdate,
+        rng: R,
+    ) -> Result<Self, ClusterError> {
+        if !beta.is_finite() {
+            return Err(ClusterError::NonFiniteBeta(beta));
+        }
+        state.check_params(&params)?;
+        Ok(Self {
+            state,
+            params,
+            beta,
+            steps,
+            update,
+            flipped_spins: 0,
+            rng,
+        })
+    }
+
+    pub fn wolff(
+        state: S,
+        params: S::Params,
+        beta: f64,
+        steps: usize,
+        rng: R,
+    ) -> Result<Self, ClusterError> {
+        Self::new(state, params, beta, steps, ClusterUpdate::Wolff, rng)
+    }
+
+    pub fn swendsen_wang(
+        state: S,
+        params: S::Params,
+        beta: f64,
+        steps: usize,
+        rng: R,
+    ) -> Result<Self, ClusterError> {
+        Self::new(state, params, beta, steps, ClusterUpdate::SwendsenWang, rng)
+    }
+
+    pub fn energy(&mut self) -> f64 {
+        self.state.energy(&mut self.params)
+    }
+
+    /// One cluster update, returns the number of flipped spins
+    pub fn step(&mut self) -> usize {
+        let flipped = match self.update {
+            ClusterUpdate::Wolff => self.wolff_step(),
+            ClusterUpdate::SwendsenWang => self.swendsen_wang_step(),
+        };
+        self.flipped_spins += flipped;
+        flipped
+    }
+
+    fn bonded(&mut self, a: usize, b: usize) -> bool {
+        let p = 1.0 - (-self.beta * self.state.bond_energy(a, b, &self.params)).exp();
+        self.rng.random::<f64>() < p
+    }
+
+    fn wolff_step(&mut self) -> usize {
+        let sites = self.state.sites();
+        if sites == 0 {
+            return 0;
+        }
+        let seed = self.rng.random_range(0..sites);
+        let old = self.state.spin(seed);
+        let new = self.state.flipped(old, &mut self.rng);
+
+        let mut in_cluster = vec![false; sites];
+        in_cluster[seed] = true;
+        let mut frontier = vec![seed];
+        let mut size = 0;
+        while let Some(site) = frontier.pop() {
+            self.state.set_spin(site, new);
+            size += 1;
+            let neighbours: Vec<usize> = self.state.neighbours(site).collect();
+            for neighbour in neighbours {
+                if !in_cluster[neighbour]
+                    && self.state.spin(neighbour) == old
+                    && self.bonded(site, neighbour)
+                {
+                    in_cluster[neighbour] = true;
+                    frontier.push(neighbour);
+                }
+            }
+        }
+        size
+    }
+
+    fn swendsen_wang_step(&mut self) -> usize {
+        let sites = self.state.sites();
+        let mut clusters = DisjointSets::new(sites);
+        for site in 0..sites {
+            let neighbours: Vec<usize> = self.state.neighbours(site).collect();
+            for neighbour in neighbours {
+                // each bond once, from its lowest site
+                if neighbour > site
+                    && self.state.spin(site) == self.state.spin(neighbour)
+                    && self.bonded(site, neighbour)
+                {
+                    clusters.union(site, neighbour);
+                }
+            }
+        }
+
+        // the new spin of each cluster, decided at its root
+        let mut new_spins: Vec<Option<S::Spin>> = vec![None; sites];
+        for root in (0..sites).filter(|&site| clusters.find(site) == site) {
+            if self.rng.random::<bool>() {
+                let spin = self.state.spin(root);
+                new_spins[root] = Some(self.state.flipped(spin, &mut self.rng));
+            }
+        }
+        let mut flipped = 0;
+        for site in 0..sites {
+            if let Some(spin) = new_spins[clusters.find(site)] {
+                self.state.set_spin(site, spin);
+                flipped += 1;
+            }
+        }
+        flipped
+    }
+
+    /// Mean number of spins flipped per step
+    pub fn mean_flipped(&self) -> f64 {
+        self.flipped_spins as f64 / self.steps as f64
+    }
+
+    pub fn run_empty(&mut self) {
+        for _ in 0..self.steps {
+            self.step();
+        }
+    }
+
+    /// Runs with an observer built from its default
+    pub fn run_with<O: Observer<S> + Default>(&mut self) -> Vec<O::Observation> {
+        self.run_with_observer(&O::default())
+    }
+
+    pub fn run_with_observer<O: Observer<S>>(&mut self, observer: &O) -> Vec<O::Observation> {
+        let mut measures: Vec<O::Observation> = Vec::new();
+        self.run_with_sink(observer, &mut measures);
+        measures
+    }
+
+    /// Streams the observations into a sink instead of collecting them
+    pub fn run_with_sink<O, K>(&mut self, observer: &O, sink: &mut K)
+    where
+        O: Observer<S>,
+        K: Sink<O::Observation>,
+    {
+        let cadence = observer.cadence();
+        for i in 0..self.steps {
+            if cadence.measures(i) {
+                sink.push(i, observer.measure(&self.state, &self.params));
+            }
+            self.step();
+        }
+    }
+}
+
+/// Union-find with path halving, to label the Swendsen-Wang clusters
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! Grand canonical (μVT) and isothermal-isobaric (NPT) ensembles of particle systems
+//!
+//! Both wrap a [`ParticleSystem`] into another [`State`], whose changes are the
+//! displacements of the system plus insertions and removals, or volume changes.
+//! The ensembles are sampled by the usual metropolis, as their weights are
+//! folded into the energy and the [`State::proposal_log_ratio`]:
+//!
+//! - grand canonical, the energy is U - μN, and inserting a particle in a volume V
+//!   has a log ratio of ln(V / (Λ (N + 1))), removing one ln(Λ N / V),
+//!   Λ being the thermal volume (Λ³ in 3D, 1 in reduced units)
+//! - isobaric, the energy is the enthalpy U + PV, and a random walk in ln V
+//!   has a log ratio of (N + 1) ln(V' / V), from the scaled coordinates and the walk
+//!
+//! The beta of metropolis makes the acceptance factors exp(βμ) and exp(-βPΔV) right.
+//! Insertions and volume changes the system refuses, like overlaps of hard cores,
+//! are invalid moves, see [`ParticleSystem::check_position`] and [`ParticleSystem::check_scale`].
+
+use rand::Rng;
+
+use crate::{ModificationError, State, tuning::Tunable};
+
+/// A state of particles in a box, that can take and lose particles and be rescaled
+pub trait ParticleSystem: State {
+    type Position: Clone;
+
+    fn particles(&self) -> usize;
+    fn volume(&self) -> f64;
+
+    /// Uniformly distributed in the box
+    fn random_position(&self, rng: &mut impl Rng) -> Self::Position;
+
+    fn position(&self, index: usize) -> Self::Position;
+
+    /// Energy of a particle at the position with every particle but `except`,
+    /// infinite if it overlaps one
+    fn interaction_energy(
+        &self,
+        position: &Self::Position,
+        except: Option<usize>,
+        params: &mut Self::Params,
+    ) -> f64;
+
+    /// Adds a particle at the position, numbered after the others
+    fn insert(&mut self, position: Self::Position);
+
+    /// Removes a particle like [`Vec::swap_remove`], the last particle takes its number
+    fn remove(&mut self, index: usize) -> Self::Position;
+
+    /// Rescales the box and the positions in it, multiplying the volume by `factor`
+    fn scale_volume(&mut self, factor: f64);
+
+    /// Whether a particle can be at the position, next to every particle but `except`.
+    /// Ok by default, systems with hard cores refuse the overlaps.
+    fn check_position(
+        &self,
+        _position: &Self::Position,
+        _except: Option<usize>,
+        _params: &Self::Params,
+    ) -> Result<(), ModificationError> {
+        Ok(())
+    }
+
+    /// Whether the box can be rescaled by `factor`, like [`check_position`](Self::check_position)
+    fn check_scale(&self, _factor: f64, _params: &Self::Params) -> Result<(), ModificationError> {
+        Ok(())
+    }
+}
+
+/// Params of a grand canonical system
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrandCanonicalParams<P> {
+    /// params of the system
+    pub system: P,
+    /// chemical potential
+    pub mu: f64,
+    /// Λ^d, with Λ the thermal wavelength, 1 in reduced units
+    pub thermal_volume: f64,
+}
+
+impl<P> GrandCanonicalParams<P> {
+    /// Reduced units, Λ = 1
+    pub fn new(system: P, mu: f64) -> Self {
+        Self {
+            system,
+            mu,
+            thermal_volume: 1.0,
+        }
+    }
+}
+
+/// Change of a grand canonical system
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrandCanonicalChange<C, X> {
+    /// a change of the system
+    Displace(C),
+    /// a new particle at the position
+    Insert(X),
+    /// the particle at the index, that was at the position, is gone
+    Remove { index: usize, position: X },
+}
+
+/// μVT ensemble of a particle system, see the [module docs](self)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrandCanonical<S> {
+    pub system: S,
+    /// probability of proposing an insertion or a removal (half and half)
+    /// instead of a change of the system, 0.5 by default
+    pub exchange_weight: f64,
+}
+
+impl<S: ParticleSystem> GrandCanonical<S> {
+    pub fn new(system: S) -> Self {
+        Self {
+            system,
+            exchange_weight: 0.5,
+        }
+    }
+
+    pub fn with_exchange_weight(mut self, exchange_weight: f64) -> Self {
+        self.exchange_weight = exchange_weight;
+        self
+    }
+
+    /// Particles per unit volume
+    pub fn density(&self) -> f64 {
+        self.system.particles() as f64 / self.system.volume()
+    }
+}
+
+impl<S: ParticleSystem> State for GrandCanonical<S> {
+    type Params = GrandCanonicalParams<S::Params>;
+    type Change = GrandCanonicalChange<S::Change, S::Position>;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.system.energy(&mut params.system) - params.mu * self.system.particles() as f64
+    }
+
+    /// Without particles, displacements and removals are proposed as removals
+    /// rejected as invalid, so the insertions keep their probability
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        let n = self.system.particles();
+        let exchange = rng.random_bool(self.exchange_weight);
+        if exchange && rng.random_bool(0.5) {
+            return GrandCanonicalChange::Insert(self.system.random_position(rng));
+        }
+        if n == 0 {
+            let position = self.system.random_position(rng);
+            return GrandCanonicalChange::Remove { index: 0, position };
+        }
+        if exchange {
+            let index = rng.random_range(0..n);
+            let position = self.system.position(index);
+            GrandCanonicalChange::Remove { index, position }
+        } else {
+            GrandCanonicalChange::Displace(self.system.propose_change(rng))
+        }
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        match change {
+            GrandCanonicalChange::Displace(change) => self.system.apply_change(change),
+            GrandCanonicalChange::Insert(position) => self.system.insert(position),
+            GrandCanonicalChange::Remove { index, .. } => {
+                self.system.remove(index);
+            }
+        }
+    }
+
+    /// A reverted removal puts the particle back as the last one
+    fn revert_change(&mut self, change: Self::Change) {
+        match change {
+            GrandCanonicalChange::Displace(change) => self.system.revert_change(change),
+            GrandCanonicalChange::Insert(_) => {
+                self.system.remove(self.system.particles() - 1);
+            }
+            GrandCanonicalChange::Remove { position, .. } => self.system.insert(position),
+        }
+    }
+
+    fn check_change(
+        &self,
+        change: &Self::Change,
+        params: &Self::Params,
+    ) -> Result<(), ModificationError> {
+        match change {
+            GrandCanonicalChange::Displace(change) => {
+                self.system.check_change(change, &params.system)
+            }
+            GrandCanonicalChange::Insert(position) => {
+                self.system.check_position(position, None, &params.system)
+            }
+            GrandCanonicalChange::Remove { index, .. } => {
+                if *index < self.system.particles() {
+                    Ok(())
+                } else {
+                    Err(ModificationError::new("there's no particle to remove"))
+                }
+            }
+        }
+    }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        match change {
+            GrandCanonicalChange::Displace(change) => {
+                self.system.delta_energy(change, &mut params.system)
+            }
+            GrandCanonicalChange::Insert(position) => Some(
+                self.system
+                    .interaction_energy(position, None, &mut params.system)
+                    - params.mu,
+            ),
+            GrandCanonicalChange::Remove { index, position } => Some(
+                params.mu
+                    - self
+                        .system
+                        .interaction_energy(position, Some(*index), &mut params.system),
+            ),
+        }
+    }
+
+    fn proposal_log_ratio(&self, change: &Self::Change, params: &Self::Params) -> f64 {
+        let (n, volume) = (self.system.particles() as f64, self.system.volume());
+        match change {
+            GrandCanonicalChange::Displace(change) => {
+                self.system.proposal_log_ratio(change, &params.system)
+            }
+            GrandCanonicalChange::Insert(_) => (volume / (params.thermal_volume * (n + 1.0))).ln(),
+            GrandCanonicalChange::Remove { .. } => (params.thermal_volume * n / volume).ln(),
+        }
+    }
+}
+
+impl<S: ParticleSystem + Tunable> Tunable for GrandCanonical<S> {
+    fn scale(&self) -> f64 {
+        self.system.scale()
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.system.set_scale(scale);
+    }
+}
+
+/// Params of an isobaric system
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IsobaricParams<P> {
+    /// params of the system
+    pub system: P,
+    pub pressure: f64,
+}
+
+/// Change of an isobaric system
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum IsobaricChange<C> {
+    /// a change of the system
+    Displace(C),
+    /// the box is rescaled from one volume to another
+    Volume { from: f64, to: f64 },
+}
+
+/// NPT ensemble of a particle system, see the [module docs](self)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Isobaric<S> {
+    pub system: S,
+    /// probability of proposing a volume change instead of a change of the system.
+    /// 1 / (N + 1) by default, about one volume change per sweep
+    pub volume_weight: f64,
+    /// max change of ln V of a proposed volume change, 0.1 by default
+    pub volume_scale: f64,
+}
+
+impl<S: ParticleSystem> Isobaric<S> {
+    pub fn new(system: S) -> Self {
+        let volume_weight = 1.0 / (system.particles() + 1) as f64;
+        Self {
+            system,
+            volume_weight,
+            volume_scale: 0.1,
+        }
+    }
+
+    pub fn with_volume_weight(mut self, volume_weight: f64) -> Self {
+        self.volume_weight = volume_weight;
+        self
+    }
+
+    pub fn with_volume_scale(mut self, volume_scale: f64) -> Self {
+        self.volume_scale = volume_scale;
+        self
+    }
+
+    /// Particles per unit volume
+    pub fn density(&self) -> f64 {
+        self.system.particles() as f64 / self.system.volume()
+    }
+}
+
+impl<S: ParticleSystem> State for Isobaric<S> {
+    type Params = IsobaricParams<S::Params>;
+    type Change = IsobaricChange<S::Change>;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.system.energy(&mut params.system) + params.pressure * self.system.volume()
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        if self.system.particles() > 0 && !rng.random_bool(self.volume_weight) {
+            return IsobaricChange::Displace(self.system.propose_change(rng));
+        }
+        let from = self.system.volume();
+        let to = from * (self.volume_scale * rng.random_range(-1.0..1.0)).exp();
+        IsobaricChange::Volume { from, to }
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        match change {
+            IsobaricChange::Displace(change) => self.system.apply_change(change),
+            IsobaricChange::Volume { from, to } => self.system.scale_volume(to / from),
+        }
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        match change {
+            IsobaricChange::Displace(change) => self.system.revert_change(change),
+            IsobaricChange::Volume { from, to } => self.system.scale_volume(from / to),
+        }
+    }
+
+    fn check_change(
+        &self,
+        change: &Self::Change,
+        params: &Self::Params,
+    ) -> Result<(), ModificationError> {
+        match change {
+            IsobaricChange::Displace(change) => self.system.check_change(change, &params.system),
+            IsobaricChange::Volume { from, to } => {
+                self.system.check_scale(to / from, &params.system)
+            }
+        }
+    }
+
+    /// Volume changes move every particle, their energy is computed again
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        match change {
+            IsobaricChange::Displace(change) => {
+                self.system.delta_energy(change, &mut params.system)
+            }
+            IsobaricChange::Volume { .. } => None,
+        }
+    }
+
+    fn proposal_log_ratio(&self, change: &Self::Change, params: &Self::Params) -> f64 {
+        match change {
+            IsobaricChange::Displace(change) => {
+                self.system.proposal_log_ratio(change, &params.system)
+            }
+            IsobaricChange::Volume { from, to } => {
+                (self.system.particles() + 1) as f64 * (to / from).ln()
+            }
+        }
+    }
+}
+
+impl<S: ParticleSystem + Tunable> Tunable for Isobaric<S> {
+    fn scale(&self) -> f64 {
+        self.system.scale()
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.system.set_scale(scale);
+    }
+}
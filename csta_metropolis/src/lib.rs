@@ -1,32 +1,109 @@
 //! This module is for metropoli + montecarlo simulations
 
-use crate::observer::*;
+use crate::acceptance::{AcceptanceRule, MetropolisRule};
+use crate::moves::MoveSet;
+use crate::observer::{sink::Sink, *};
 use rand::{Rng, rngs::ThreadRng};
-
+use std::fmt;
+
+pub mod acceptance;
+pub mod analysis;
+pub mod annealing;
+pub mod builder;
+pub mod chains;
+#[cfg(feature = "serde")]
+pub mod checkpoint;
+pub mod cluster;
+pub mod ensembles;
+pub mod moves;
 pub mod observer;
+pub mod tempering;
+pub mod thermalization;
+pub mod tuning;
+pub mod wang_landau;
+
+/// A change that can't be made, like one that overlaps two particles or leaves the box
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModificationError {
+    pub reason: String,
+}
+
+impl ModificationError {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ModificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid change: {}", self.reason)
+    }
+}
+
+impl std::error::Error for ModificationError {}
 
 pub trait State {
     type Params;
     type Change: Clone;
-    // todo: consider modification error
-    // maybe is better to propose_change to be lightweight, and offload most
-    // of the computation to apply or revert change. Returning an error can be useful for
-    // solving the issue (thinking of panics inside apply_change and revert_change)
-    // type ModificationError
 
     fn energy(&self, params: &mut Self::Params) -> f64;
     fn propose_change(&self, rng: &mut impl Rng) -> Self::Change;
-    fn apply_change(&mut self, change: Self::Change) /* -> ModificationError */;
-    fn revert_change(&mut self, change: Self::Change) /* -> ModificationError */;
+    fn apply_change(&mut self, change: Self::Change);
+    fn revert_change(&mut self, change: Self::Change);
+
+    /// Whether the change can be made, checked by metropolis before anything else.
+    /// An error counts as a rejection, see [`Metropolis::invalid_moves`].
+    fn check_change(
+        &self,
+        _change: &Self::Change,
+        _params: &Self::Params,
+    ) -> Result<(), ModificationError> {
+        Ok(())
+    }
+
+    /// Fallible apply_change, for states that only find out while applying it.
+    /// On error the state has to be left as it was, and it counts as a rejection.
+    fn try_apply_change(&mut self, change: Self::Change) -> Result<(), ModificationError> {
+        self.apply_change(change);
+        Ok(())
+    }
+
+    /// Fallible revert_change. Reverting an applied change can't be rejected,
+    /// so metropolis panics with the error, as the state can't be restored.
+    fn try_revert_change(&mut self, change: Self::Change) -> Result<(), ModificationError> {
+        self.revert_change(change);
+        Ok(())
+    }
+
+    /// Energy difference that applying `change` would produce, without applying it.
+    /// Returning None (the default) makes metropolis fall back to computing
+    /// the energy before and after the change.
+    fn delta_energy(&self, _change: &Self::Change, _params: &mut Self::Params) -> Option<f64> {
+        None
+    }
+
+    /// Hastings correction ln q(x|x') - ln q(x'|x) of a change from this state x to x',
+    /// q being the probability of proposing a change.
+    /// Zero (the default) for symmetric proposals.
+    fn proposal_log_ratio(&self, _change: &Self::Change, _params: &Self::Params) -> f64 {
+        0.0
+    }
 }
 
-pub struct Metropolis<S: State, R: Rng> {
+pub struct Metropolis<S: State, R: Rng, A: AcceptanceRule = MetropolisRule> {
     pub state: S,
     pub params: S::Params,
     pub beta: f64,
     pub steps: usize,
     pub accepted_moves: usize,
+    /// proposals rejected because the change couldn't be made, they aren't accepted moves either
+    pub invalid_moves: usize,
     pub rng: R,
+    pub rule: A,
+    moves: Option<MoveSet<S>>,
+    energy: f64,
 }
 
 impl<S> Metropolis<S, ThreadRng>
@@ -34,12 +111,14 @@ where
     S: State + Default,
     S::Params: Default,
 {
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_steps(beta: f64, steps: usize) -> Self {
-        Self::with_rng(beta, steps, rand::rng())
+        Self::new(S::default(), S::Params::default(), beta, steps, rand::rng())
     }
 
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_steps_no_beta(steps: usize) -> Self {
-        Self::with_steps(1.0, steps)
+        Self::new(S::default(), S::Params::default(), 1.0, steps, rand::rng())
     }
 }
 
@@ -48,22 +127,26 @@ where
     S: State,
     S::Params: Default,
 {
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_state(state: S, beta: f64, steps: usize) -> Self {
-        Self::with_state_params(state, S::Params::default(), beta, steps)
+        Self::new(state, S::Params::default(), beta, steps, rand::rng())
     }
 
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_state_no_beta(state: S, steps: usize) -> Self {
-        Self::with_state(state, 1.0, steps)
+        Self::new(state, S::Params::default(), 1.0, steps, rand::rng())
     }
 }
 
 impl<S: State> Metropolis<S, ThreadRng> {
+    #[deprecated(note = "use `MetropolisBuilder::with_params`")]
     pub fn with_state_params(state: S, params: S::Params, beta: f64, steps: usize) -> Self {
-        Self::with_all(state, params, beta, steps, rand::rng())
+        Self::new(state, params, beta, steps, rand::rng())
     }
 
+    #[deprecated(note = "use `MetropolisBuilder::with_params`")]
     pub fn with_state_params_no_beta(state: S, params: S::Params, steps: usize) -> Self {
-        Self::with_state_params(state, params, 1.0, steps)
+        Self::new(state, params, 1.0, steps, rand::rng())
     }
 }
 
@@ -73,12 +156,14 @@ where
     S::Params: Default,
     R: Rng,
 {
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_state_rng(state: S, beta: f64, steps: usize, rng: R) -> Self {
-        Self::with_all(state, S::Params::default(), beta, steps, rng)
+        Self::new(state, S::Params::default(), beta, steps, rng)
     }
 
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_state_rng_no_beta(state: S, steps: usize, rng: R) -> Self {
-        Self::with_state_rng(state, 1.0, steps, rng)
+        Self::new(state, S::Params::default(), 1.0, steps, rng)
     }
 }
 
@@ -88,43 +173,133 @@ where
     S::Params: Default,
     R: Rng,
 {
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_rng(beta: f64, steps: usize, rng: R) -> Self {
-        Self::with_all(S::default(), S::Params::default(), beta, steps, rng)
+        Self::new(S::default(), S::Params::default(), beta, steps, rng)
     }
 
+    #[deprecated(note = "use `Metropolis::builder()`")]
     pub fn with_rng_no_beta(steps: usize, rng: R) -> Self {
-        Self::with_rng(1.0, steps, rng)
+        Self::new(S::default(), S::Params::default(), 1.0, steps, rng)
     }
 }
 
 impl<S: State, R: Rng> Metropolis<S, R> {
+    #[deprecated(note = "use `MetropolisBuilder::with_params`")]
     pub fn with_all(state: S, params: S::Params, beta: f64, steps: usize, rng: R) -> Self {
+        Self::new(state, params, beta, steps, rng)
+    }
+
+    #[deprecated(note = "use `MetropolisBuilder::with_params`")]
+    pub fn with_all_no_beta(state: S, params: S::Params, steps: usize, rng: R) -> Self {
+        Self::new(state, params, 1.0, steps, rng)
+    }
+
+    /// Unchecked, the builder validates the settings before
+    pub(crate) fn new(state: S, mut params: S::Params, beta: f64, steps: usize, rng: R) -> Self {
+        let energy = state.energy(&mut params);
         Self {
             state,
             params,
             beta,
             steps,
             accepted_moves: 0,
+            invalid_moves: 0,
             rng,
+            rule: MetropolisRule,
+            moves: None,
+            energy,
         }
     }
+}
 
-    pub fn with_all_no_beta(state: S, params: S::Params, steps: usize, rng: R) -> Self {
-        Self::with_all(state, params, 1.0, steps, rng)
+impl<S: State, R: Rng, A: AcceptanceRule> Metropolis<S, R, A> {
+    /// Same metropolis, accepting changes with another rule
+    pub fn with_rule<B: AcceptanceRule>(self, rule: B) -> Metropolis<S, R, B> {
+        Metropolis {
+            state: self.state,
+            params: self.params,
+            beta: self.beta,
+            steps: self.steps,
+            accepted_moves: self.accepted_moves,
+            invalid_moves: self.invalid_moves,
+            rng: self.rng,
+            rule,
+            moves: self.moves,
+            energy: self.energy,
+        }
+    }
+
+    /// Energy of the current state, kept up to date by [`Metropolis::step`].
+    pub fn energy(&self) -> f64 {
+        self.energy
     }
 
-    /// Metropolis algorithm
+    /// Recomputes the cached energy, needed after modifying `state` or `params` by hand.
+    pub fn refresh_energy(&mut self) -> f64 {
+        self.energy = self.state.energy(&mut self.params);
+        self.energy
+    }
+
+    /// Metropolis-Hastings algorithm, with the acceptance given by the rule
+    /// and the change proposed by the move set, if there is one.
+    /// If the state knows its delta_energy, the change is only applied when accepted.
     pub fn step(&mut self) {
-        let change = self.state.propose_change(&mut self.rng);
+        let (index, change) = match &self.moves {
+            Some(moves) => {
+                let (index, change) = moves.propose(&self.state, &mut self.rng);
+                (Some(index), change)
+            }
+            None => (None, self.state.propose_change(&mut self.rng)),
+        };
+        let accepted = self.try_change(change);
+        if let (Some(moves), Some(index)) = (&mut self.moves, index) {
+            moves.record(index, accepted);
+        }
+    }
+
+    fn try_change(&mut self, change: S::Change) -> bool {
+        if self.state.check_change(&change, &self.params).is_err() {
+            self.invalid_moves += 1;
+            return false;
+        }
+        let ln_q_ratio = self.state.proposal_log_ratio(&change, &self.params);
+        if let Some(delta_energy) = self.state.delta_energy(&change, &mut self.params) {
+            if !self.accept(delta_energy, ln_q_ratio) {
+                return false;
+            }
+            if self.state.try_apply_change(change).is_err() {
+                self.invalid_moves += 1;
+                return false;
+            }
+            self.energy += delta_energy;
+            self.accepted_moves += 1;
+            return true;
+        }
+
         let old_energy = self.state.energy(&mut self.params);
-        self.state.apply_change(change.clone());
+        if self.state.try_apply_change(change.clone()).is_err() {
+            self.invalid_moves += 1;
+            return false;
+        }
         let new_energy = self.state.energy(&mut self.params);
         let delta_energy = new_energy - old_energy;
-        if delta_energy < 0.0 || self.rng.random::<f64>() < (-self.beta * delta_energy).exp() {
+        let accepted = self.accept(delta_energy, ln_q_ratio);
+        if accepted {
+            self.energy = new_energy;
             self.accepted_moves += 1;
         } else {
-            self.state.revert_change(change);
+            self.energy = old_energy;
+            if let Err(error) = self.state.try_revert_change(change) {
+                panic!("a rejected change couldn't be reverted, {error}");
+            }
         }
+        accepted
+    }
+
+    fn accept(&mut self, delta_energy: f64, ln_q_ratio: f64) -> bool {
+        let probability = self.rule.probability(delta_energy, self.beta, ln_q_ratio);
+        probability >= 1.0 || self.rng.random::<f64>() < probability
     }
 
     pub fn run_empty(&mut self) {
@@ -133,118 +308,80 @@ impl<S: State, R: Rng> Metropolis<S, R> {
         }
     }
 
-    pub fn run_with<O: Observer<S>>(&mut self) -> Vec<O::Observation> {
+    /// Runs with an observer built from its default.
+    /// Several observers are run together with [`Metropolis::run_with_set`] and
+    /// [`Metropolis::run_with_observers`], which replace `run_with_2` to `run_with_4`
+    /// and take up to 12 observers
+    pub fn run_with<O: Observer<S> + Default>(&mut self) -> Vec<O::Observation> {
+        self.run_with_observer(&O::default())
+    }
+
+    pub fn run_with_observer<O: Observer<S>>(&mut self, observer: &O) -> Vec<O::Observation> {
         let mut measures: Vec<O::Observation> = Vec::new();
-        for i in 0..self.steps {
-            if i > O::after() && i % O::every() == 0 {
-                measures.push(O::measure(&self.state, &self.params));
-            }
-            self.step();
-        }
+        self.run_with_sink(observer, &mut measures);
         measures
     }
 
-    pub fn run_with_2<O1, O2>(&mut self) -> (Vec<O1::Observation>, Vec<O2::Observation>)
+    /// Streams the observations into a sink instead of collecting them
+    pub fn run_with_sink<O, K>(&mut self, observer: &O, sink: &mut K)
     where
-        O1: Observer<S>,
-        O2: Observer<S>,
+        O: Observer<S>,
+        K: Sink<O::Observation>,
     {
-        let mut o1_measures: Vec<O1::Observation> = Vec::new();
-        let mut o2_measures: Vec<O2::Observation> = Vec::new();
-
+        let cadence = observer.cadence();
         for i in 0..self.steps {
-            if i > O1::after() && i % O1::every() == 0 {
-                o1_measures.push(O1::measure(&self.state, &self.params));
-            }
-            if i > O2::after() && i % O2::every() == 0 {
-                o2_measures.push(O2::measure(&self.state, &self.params));
+            if cadence.measures(i) {
+                sink.push(
+                    i,
+                    observer.measure_with_energy(&self.state, &self.params, self.energy),
+                );
             }
             self.step();
         }
-
-        (o1_measures, o2_measures)
     }
 
-    #[allow(clippy::type_complexity)]
-    pub fn run_with_3<O1, O2, O3>(
-        &mut self,
-    ) -> (
-        Vec<O1::Observation>,
-        Vec<O2::Observation>,
-        Vec<O3::Observation>,
-    )
+    /// Runs with a tuple of observers built from their defaults,
+    /// like `run_with_set::<(Energy, Magnetization)>()`, returning a tuple of observations.
+    /// Same as [`Metropolis::run_with_observers`] with `Set::default()`,
+    /// and the same cap of 12 observers, see [`ObserverSet`]
+    pub fn run_with_set<Set>(&mut self) -> Set::Observations
     where
-        O1: Observer<S>,
-        O2: Observer<S>,
-        O3: Observer<S>,
+        Set: ObserverSet<S> + ObserveInto<S, <Set as ObserverSet<S>>::Observations> + Default,
     {
-        let mut o1_measures: Vec<O1::Observation> = Vec::new();
-        let mut o2_measures: Vec<O2::Observation> = Vec::new();
-        let mut o3_measures: Vec<O3::Observation> = Vec::new();
-
-        for i in 0..self.steps {
-            if i > O1::after() && i % O1::every() == 0 {
-                o1_measures.push(O1::measure(&self.state, &self.params));
-            }
-            if i > O2::after() && i % O2::every() == 0 {
-                o2_measures.push(O2::measure(&self.state, &self.params));
-            }
-            if i > O3::after() && i % O3::every() == 0 {
-                o3_measures.push(O3::measure(&self.state, &self.params));
-            }
-            self.step();
-        }
-
-        (o1_measures, o2_measures, o3_measures)
+        self.run_with_observers(&Set::default())
     }
 
-    #[allow(clippy::type_complexity)]
-    pub fn run_with_4<O1, O2, O3, O4>(
-        &mut self,
-    ) -> (
-        Vec<O1::Observation>,
-        Vec<O2::Observation>,
-        Vec<O3::Observation>,
-        Vec<O4::Observation>,
-    )
+    /// Runs with a tuple of up to 12 observers, returning a tuple of observations,
+    /// the same each observer gets when run alone from the same state and rng
+    pub fn run_with_observers<Set>(&mut self, observers: &Set) -> Set::Observations
     where
-        O1: Observer<S>,
-        O2: Observer<S>,
-        O3: Observer<S>,
-        O4: Observer<S>,
+        Set: ObserverSet<S> + ObserveInto<S, <Set as ObserverSet<S>>::Observations>,
     {
-        let mut o1_measures: Vec<O1::Observation> = Vec::new();
-        let mut o2_measures: Vec<O2::Observation> = Vec::new();
-        let mut o3_measures: Vec<O3::Observation> = Vec::new();
-        let mut o4_measures: Vec<O4::Observation> = Vec::new();
+        let mut measures = observers.empty();
+        self.run_with_sinks(observers, &mut measures);
+        measures
+    }
 
+    /// Streams the observations of each observer into its sink of the tuple
+    pub fn run_with_sinks<Set, K>(&mut self, observers: &Set, sinks: &mut K)
+    where
+        Set: ObserveInto<S, K>,
+    {
+        let cadences = observers.cadences();
         for i in 0..self.steps {
-            if i > O1::after() && i % O1::every() == 0 {
-                o1_measures.push(O1::measure(&self.state, &self.params));
-            }
-            if i > O2::after() && i % O2::every() == 0 {
-                o2_measures.push(O2::measure(&self.state, &self.params));
-            }
-            if i > O3::after() && i % O3::every() == 0 {
-                o3_measures.push(O3::measure(&self.state, &self.params));
-            }
-            if i > O4::after() && i % O4::every() == 0 {
-                o4_measures.push(O4::measure(&self.state, &self.params));
-            }
+            observers.observe(i, &cadences, &self.state, &self.params, self.energy, sinks);
             self.step();
         }
-
-        (o1_measures, o2_measures, o3_measures, o4_measures)
     }
 
     /// Makes a run for a vec of observers
     /// All observers needs to have the same Observation type
-    /// And need to be dyn-capable
-    /// All Observers are made dyn using DynObserver
+    /// Each one keeps its own cadence
     pub fn run_with_n<Obs>(
         &mut self,
-        obs: Vec<Box<dyn DynObserver<S, Observation = Obs>>>,
+        obs: Vec<Box<dyn Observer<S, Observation = Obs>>>,
     ) -> Vec<Vec<Obs>> {
+        let cadences: Vec<Cadence> = obs.iter().map(|o| o.cadence()).collect();
         let mut measures: Vec<Vec<Obs>> = Vec::new();
         for _ in obs.iter() {
             measures.push(Vec::new());
@@ -252,8 +389,8 @@ impl<S: State, R: Rng> Metropolis<S, R> {
 
         for i in 0..self.steps {
             for (j, o) in obs.iter().enumerate() {
-                if i > o.after() && i % o.every() == 0 {
-                    measures[j].push(o.measure(&self.state, &self.params));
+                if cadences[j].measures(i) {
+                    measures[j].push(o.measure_with_energy(&self.state, &self.params, self.energy));
                 }
             }
             self.step();
@@ -265,7 +402,18 @@ impl<S: State, R: Rng> Metropolis<S, R> {
         self.accepted_moves as f64 / self.steps as f64
     }
 
+    /// Rejected for any reason, energetic or invalid change
     pub fn rejected_rate(&self) -> f64 {
         1.0 - self.accepted_rate()
     }
+
+    /// Rejected because the change couldn't be made
+    pub fn invalid_rate(&self) -> f64 {
+        self.invalid_moves as f64 / self.steps as f64
+    }
+
+    /// Rejected by the acceptance rule, from valid changes
+    pub fn energetic_rejected_rate(&self) -> f64 {
+        self.rejected_rate() - self.invalid_rate()
+    }
 }
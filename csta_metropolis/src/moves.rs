@@ -0,0 +1,183 @@
+//! Several kinds of proposals for the same state, like translations, rotations and swaps
+//!
+//! Each step one move is chosen with probability proportional to its weight, and
+//! proposes the change in place of [`State::propose_change`]. Metropolis counts the
+//! attempted and accepted changes of each move, so each one can be tuned on its own.
+//! The weights don't depend on the state, so a mix of symmetric moves is symmetric,
+//! otherwise [`State::proposal_log_ratio`] has to account for the move that was chosen.
+
+use std::fmt;
+
+use rand::{Rng, RngCore};
+
+use crate::{Metropolis, State, acceptance::AcceptanceRule};
+
+type Propose<S> = Box<dyn Fn(&S, &mut dyn RngCore) -> <S as State>::Change + Send + Sync>;
+
+/// A named generator of changes, with its weight and counts
+pub struct Move<S: State> {
+    pub name: String,
+    pub weight: f64,
+    pub attempted: usize,
+    pub accepted: usize,
+    propose: Propose<S>,
+}
+
+impl<S: State> Move<S> {
+    /// Accepted over attempted changes, NaN if never attempted
+    pub fn acceptance_rate(&self) -> f64 {
+        self.accepted as f64 / self.attempted as f64
+    }
+}
+
+impl<S: State> fmt::Debug for Move<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Move")
+            .field("name", &self.name)
+            .field("weight", &self.weight)
+            .field("attempted", &self.attempted)
+            .field("accepted", &self.accepted)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Moves of a state, see the [module docs](self)
+#[derive(Debug)]
+pub struct MoveSet<S: State> {
+    moves: Vec<Move<S>>,
+}
+
+impl<S: State> Default for MoveSet<S> {
+    fn default() -> Self {
+        Self { moves: Vec::new() }
+    }
+}
+
+impl<S: State> MoveSet<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a move, chosen with probability weight / total weight.
+    /// Panics if the weight is negative or not finite.
+    pub fn with<F>(mut self, name: impl Into<String>, weight: f64, propose: F) -> Self
+    where
+        F: Fn(&S, &mut dyn RngCore) -> S::Change + Send + Sync + 'static,
+    {
+        assert!(
+            weight.is_finite() && weight >= 0.0,
+            "move weights must be finite and not negative, got {weight}"
+        );
+        self.moves.push(Move {
+            name: name.into(),
+            weight,
+            attempted: 0,
+            accepted: 0,
+            propose: Box::new(propose),
+        });
+        self
+    }
+
+    pub fn moves(&self) -> &[Move<S>] {
+        &self.moves
+    }
+
+    /// Move registered with that name
+    pub fn get(&self, name: &str) -> Option<&Move<S>> {
+        self.moves.iter().find(|m| m.name == name)
+    }
+
+    /// Zeroes the counts of every move, to measure the rates of a new run
+    pub fn reset_counts(&mut self) {
+        self.moves.iter_mut().for_each(|m| {
+            m.attempted = 0;
+            m.accepted = 0;
+        });
+    }
+
+    fn counts(&self) -> Vec<(usize, usize)> {
+        self.moves
+            .iter()
+            .map(|m| (m.attempted, m.accepted))
+            .collect()
+    }
+
+    fn set_counts(&mut self, counts: &[(usize, usize)]) {
+        for (m, &(attempted, accepted)) in self.moves.iter_mut().zip(counts) {
+            m.attempted = attempted;
+            m.accepted = accepted;
+        }
+    }
+
+    /// Chooses a move and proposes its change, returning the index of the move.
+    /// Panics if there are no moves or all weights are zero.
+    pub fn propose(&self, state: &S, rng: &mut impl Rng) -> (usize, S::Change) {
+        let total: f64 = self.moves.iter().map(|m| m.weight).sum();
+        assert!(total > 0.0, "a move set needs a move with positive weight");
+        let mut r = rng.random::<f64>() * total;
+        let mut index = self.moves.iter().rposition(|m| m.weight > 0.0).unwrap();
+        for (i, m) in self.moves.iter().enumerate() {
+            if r < m.weight {
+                index = i;
+                break;
+            }
+            r -= m.weight;
+        }
+        let rng: &mut dyn RngCore = rng;
+        (index, (self.moves[index].propose)(state, rng))
+    }
+
+    /// Counts an attempt of the move at `index`
+    pub fn record(&mut self, index: usize, accepted: bool) {
+        let m = &mut self.moves[index];
+        m.attempted += 1;
+        if accepted {
+            m.accepted += 1;
+        }
+    }
+}
+
+/// A state that registers its own moves
+pub trait MultiMove: State + Sized {
+    fn moves(&self) -> MoveSet<Self>;
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule> Metropolis<S, R, A> {
+    /// Same metropolis, proposing the changes with the moves of the set
+    pub fn with_moves(mut self, moves: MoveSet<S>) -> Self {
+        self.moves = Some(moves);
+        self
+    }
+
+    /// Same metropolis, proposing the changes with the moves the state registers
+    pub fn with_state_moves(self) -> Self
+    where
+        S: MultiMove,
+    {
+        let moves = self.state.moves();
+        self.with_moves(moves)
+    }
+
+    /// Moves in use with their counts, None if changes come from propose_change
+    pub fn move_set(&self) -> Option<&MoveSet<S>> {
+        self.moves.as_ref()
+    }
+
+    pub fn move_set_mut(&mut self) -> Option<&mut MoveSet<S>> {
+        self.moves.as_mut()
+    }
+
+    /// Runs `steps`, then puts back the accepted, invalid and per move counts,
+    /// for burn-in steps that don't count towards them
+    pub(crate) fn uncounted<T>(&mut self, steps: impl FnOnce(&mut Self) -> T) -> T {
+        let (accepted_moves, invalid_moves) = (self.accepted_moves, self.invalid_moves);
+        let counts = self.moves.as_ref().map(MoveSet::counts);
+        let result = steps(self);
+        self.accepted_moves = accepted_moves;
+        self.invalid_moves = invalid_moves;
+        if let (Some(moves), Some(counts)) = (self.moves.as_mut(), counts) {
+            moves.set_counts(&counts);
+        }
+        result
+    }
+}
@@ -1,43 +1,196 @@
 use crate::State;
+use sink::Sink;
+
+pub mod sink;
 
 pub trait Observer<S: State> {
     /// for energy, this is f64
     type Observation;
 
     /// Measure the state, returns an observation.
-    fn measure(state: &S, params: &S::Params) -> Self::Observation;
+    fn measure(&self, state: &S, params: &S::Params) -> Self::Observation;
+
+    /// Measure the state, given the energy the run keeps of it.
+    /// Calls [`measure`](Self::measure) by default, observers of the energy
+    /// override it so they don't compute the energy again.
+    fn measure_with_energy(&self, state: &S, params: &S::Params, energy: f64) -> Self::Observation {
+        let _ = energy;
+        self.measure(state, params)
+    }
 
-    /// every nth step will measure.
-    fn every() -> usize;
+    /// At which steps it will measure.
+    fn cadence(&self) -> Cadence;
 
-    /// only after nth step will start to measure.
-    fn after() -> usize;
+    /// Same observer, measuring at other steps.
+    fn with_cadence(self, cadence: Cadence) -> Scheduled<Self>
+    where
+        Self: Sized,
+    {
+        Scheduled {
+            observer: self,
+            cadence,
+        }
+    }
 }
 
-/// Not different from above, just has &self so it can be built into a dyn Trait
-pub trait DynObserver<S: State> {
-    type Observation;
-    fn measure(&self, state: &S, params: &S::Params) -> Self::Observation;
-    fn every(&self) -> usize;
-    fn after(&self) -> usize;
+/// Steps at which an observer measures, can be built at runtime (from a config for example)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cadence {
+    /// every nth step, only after the `after` step
+    Every { every: usize, after: usize },
+    /// `per_decade` steps logarithmically spaced for each power of 10 of steps after the `after` step,
+    /// so early (fast changing) steps are measured more often than late ones
+    Logarithmic { per_decade: usize, after: usize },
+    /// only at the given steps, sorted
+    Steps(Vec<usize>),
+}
+
+impl Cadence {
+    pub fn every(every: usize, after: usize) -> Self {
+        Cadence::Every { every, after }
+    }
+
+    pub fn logarithmic(per_decade: usize, after: usize) -> Self {
+        Cadence::Logarithmic { per_decade, after }
+    }
+
+    pub fn steps(mut steps: Vec<usize>) -> Self {
+        steps.sort_unstable();
+        steps.dedup();
+        Cadence::Steps(steps)
+    }
+
+    /// Whether the nth step is measured
+    pub fn measures(&self, step: usize) -> bool {
+        match self {
+            Cadence::Every { every, after } => step > *after && step.is_multiple_of(*every),
+            Cadence::Logarithmic { per_decade, after } => {
+                if step <= *after || *per_decade == 0 {
+                    return false;
+                }
+                let t = step - after;
+                let per_decade = *per_decade as f64;
+                let k = ((t as f64).log10() * per_decade).round();
+                // neighbouring k too, to not miss a step because of rounding
+                [k - 1.0, k, k + 1.0]
+                    .into_iter()
+                    .filter(|k| *k >= 0.0)
+                    .any(|k| 10f64.powf(k / per_decade).round() as usize == t)
+            }
+            Cadence::Steps(steps) => steps.binary_search(&step).is_ok(),
+        }
+    }
+}
+
+/// An observer with its cadence replaced
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scheduled<O> {
+    pub observer: O,
+    pub cadence: Cadence,
 }
 
-/// All Observers are DynObservers
-impl<O, S: State> DynObserver<S> for O
+impl<O, S: State> Observer<S> for Scheduled<O>
 where
     O: Observer<S>,
 {
     type Observation = O::Observation;
 
-    fn after(&self) -> usize {
-        O::after()
+    fn measure(&self, state: &S, params: &S::Params) -> Self::Observation {
+        self.observer.measure(state, params)
     }
 
-    fn every(&self) -> usize {
-        O::every()
+    fn measure_with_energy(&self, state: &S, params: &S::Params, energy: f64) -> Self::Observation {
+        self.observer.measure_with_energy(state, params, energy)
     }
 
-    fn measure(&self, state: &S, params: &<S as State>::Params) -> Self::Observation {
-        O::measure(state, params)
+    fn cadence(&self) -> Cadence {
+        self.cadence.clone()
     }
 }
+
+/// Observers measured in the same run, each with its own type of observation.
+///
+/// Implemented for flat tuples of 1 to 12 observers, not for nested tuples,
+/// since a tuple of observers can't be told apart from a tuple of sets.
+/// More observers than that need a second run, or an observer
+/// whose observation is itself a tuple.
+pub trait ObserverSet<S: State> {
+    /// a tuple with a vec of observations for each observer
+    type Observations;
+
+    /// Cadence of each observer, taken once before the run
+    fn cadences(&self) -> Vec<Cadence>;
+
+    /// Observations before the run
+    fn empty(&self) -> Self::Observations;
+}
+
+/// Sends the observations of a set into a tuple of sinks, one for each observer.
+/// A tuple of vecs is also a tuple of sinks, so every set can be observed into its Observations.
+pub trait ObserveInto<S: State, K>: ObserverSet<S> {
+    /// Measures with the observers whose cadence includes this step,
+    /// `energy` being the energy of the state
+    fn observe(
+        &self,
+        step: usize,
+        cadences: &[Cadence],
+        state: &S,
+        params: &S::Params,
+        energy: f64,
+        sinks: &mut K,
+    );
+}
+
+macro_rules! observer_tuple {
+    ($($t:ident $k:ident $i:tt),+) => {
+        impl<S: State, $($t,)+> ObserverSet<S> for ($($t,)+)
+        where
+            $($t: Observer<S>,)+
+        {
+            type Observations = ($(Vec<<$t as Observer<S>>::Observation>,)+);
+
+            fn cadences(&self) -> Vec<Cadence> {
+                vec![$(self.$i.cadence(),)+]
+            }
+
+            fn empty(&self) -> Self::Observations {
+                ($(Vec::<<$t as Observer<S>>::Observation>::new(),)+)
+            }
+        }
+
+        impl<S: State, $($t,)+ $($k,)+> ObserveInto<S, ($($k,)+)> for ($($t,)+)
+        where
+            $($t: Observer<S>,)+
+            $($k: Sink<<$t as Observer<S>>::Observation>,)+
+        {
+            fn observe(
+                &self,
+                step: usize,
+                cadences: &[Cadence],
+                state: &S,
+                params: &S::Params,
+                energy: f64,
+                sinks: &mut ($($k,)+),
+            ) {
+                $(
+                    if cadences[$i].measures(step) {
+                        sinks.$i.push(step, self.$i.measure_with_energy(state, params, energy));
+                    }
+                )+
+            }
+        }
+    };
+}
+
+observer_tuple! {A KA 0}
+observer_tuple! {A KA 0, B KB 1}
+observer_tuple! {A KA 0, B KB 1, C KC 2}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4, F KF 5}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4, F KF 5, G KG 6}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4, F KF 5, G KG 6, H KH 7}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4, F KF 5, G KG 6, H KH 7, I KI 8}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4, F KF 5, G KG 6, H KH 7, I KI 8, J KJ 9}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4, F KF 5, G KG 6, H KH 7, I KI 8, J KJ 9, K KK 10}
+observer_tuple! {A KA 0, B KB 1, C KC 2, D KD 3, E KE 4, F KF 5, G KG 6, H KH 7, I KI 8, J KJ 9, K KK 10, L KL 11}
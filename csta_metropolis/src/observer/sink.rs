@@ -0,0 +1,160 @@
+//! Sinks receive the observations as they are measured, so long runs
+//! don't need to keep every observation in memory.
+
+use std::io::{self, Write};
+
+pub trait Sink<T> {
+    /// Receives the observation measured at the nth step
+    fn push(&mut self, step: usize, observation: T);
+}
+
+/// Keeps every observation in memory, what run_with does
+impl<T> Sink<T> for Vec<T> {
+    fn push(&mut self, _step: usize, observation: T) {
+        Vec::push(self, observation);
+    }
+}
+
+impl<T, K: Sink<T> + ?Sized> Sink<T> for &mut K {
+    fn push(&mut self, step: usize, observation: T) {
+        (**self).push(step, observation);
+    }
+}
+
+/// Calls a closure with the step and the observation
+pub struct FnSink<F>(pub F);
+
+pub fn from_fn<T, F: FnMut(usize, T)>(f: F) -> FnSink<F> {
+    FnSink(f)
+}
+
+impl<T, F: FnMut(usize, T)> Sink<T> for FnSink<F> {
+    fn push(&mut self, step: usize, observation: T) {
+        (self.0)(step, observation)
+    }
+}
+
+/// Keeps only the running mean and variance (Welford's algorithm)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Accumulator {
+    count: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl Accumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Sample variance, NaN with less than 2 observations
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return f64::NAN;
+        }
+        self.m2 / (self.count - 1) as f64
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+}
+
+impl<T: Into<f64>> Sink<T> for Accumulator {
+    fn push(&mut self, _step: usize, observation: T) {
+        let x = observation.into();
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+}
+
+/// Writes `step,observation` lines, after a `step,name` header.
+/// The first io error stops the writing, and is returned by finish.
+pub struct CsvSink<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(mut writer: W, name: &str) -> Self {
+        let error = writeln!(writer, "step,{name}").err();
+        Self { writer, error }
+    }
+
+    /// Flushes the writer and gives it back, or the first error found
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<T: std::fmt::Display, W: Write> Sink<T> for CsvSink<W> {
+    fn push(&mut self, step: usize, observation: T) {
+        if self.error.is_none() {
+            self.error = writeln!(self.writer, "{step},{observation}").err();
+        }
+    }
+}
+
+/// Writes a `{"step": n, "value": observation}` json object per line.
+/// The first error stops the writing, and is returned by finish.
+#[cfg(feature = "serde")]
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "serde")]
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Flushes the writer and gives it back, or the first error found
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, W: Write> Sink<T> for JsonLinesSink<W> {
+    fn push(&mut self, step: usize, observation: T) {
+        #[derive(serde::Serialize)]
+        struct Line<T> {
+            step: usize,
+            value: T,
+        }
+
+        if self.error.is_some() {
+            return;
+        }
+        let line = Line {
+            step,
+            value: observation,
+        };
+        self.error = serde_json::to_writer(&mut self.writer, &line)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(self.writer))
+            .err();
+    }
+}
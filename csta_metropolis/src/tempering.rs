@@ -0,0 +1,139 @@
+//! Parallel tempering (replica exchange) on top of metropolis
+
+use rand::Rng;
+
+use crate::{
+    Metropolis, State,
+    acceptance::{AcceptanceRule, MetropolisRule},
+    observer::Observer,
+};
+
+/// Ladder of metropolis replicas, each at its own beta, that periodically
+/// try to exchange states between neighbouring temperatures.
+///
+/// Replicas are expected to share the same params, as only states are exchanged,
+/// the beta of each position of the ladder stays fixed.
+///
+/// For a reproducible run give each replica its own stream of a seed,
+/// see [`csta_montecarlo::seed::streams`].
+pub struct ParallelTempering<S: State, R: Rng, A: AcceptanceRule = MetropolisRule> {
+    pub replicas: Vec<Metropolis<S, R, A>>,
+    pub steps: usize,
+    /// every nth step a round of swaps is attempted
+    pub swap_every: usize,
+    pub rng: R,
+    swap_attempts: Vec<usize>,
+    swap_accepted: Vec<usize>,
+    swap_rounds: usize,
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule> ParallelTempering<S, R, A> {
+    /// The replicas steps are overwritten with `steps`, so their accepted rates stay meaningful
+    pub fn new(
+        mut replicas: Vec<Metropolis<S, R, A>>,
+        steps: usize,
+        swap_every: usize,
+        rng: R,
+    ) -> Self {
+        for replica in replicas.iter_mut() {
+            replica.steps = steps;
+        }
+        let pairs = replicas.len().saturating_sub(1);
+        Self {
+            replicas,
+            steps,
+            swap_every,
+            rng,
+            swap_attempts: vec![0; pairs],
+            swap_accepted: vec![0; pairs],
+            swap_rounds: 0,
+        }
+    }
+
+    pub fn betas(&self) -> Vec<f64> {
+        self.replicas.iter().map(|r| r.beta).collect()
+    }
+
+    /// One metropolis step on every replica, followed by a swap round if it is due
+    pub fn step(&mut self, i: usize) {
+        for replica in self.replicas.iter_mut() {
+            replica.step();
+        }
+        if self.swap_every > 0 && (i + 1).is_multiple_of(self.swap_every) {
+            self.swap();
+        }
+    }
+
+    /// Attempts to swap neighbouring replicas, alternating between even and odd pairs
+    /// so each pair is independent of the others in the same round.
+    pub fn swap(&mut self) {
+        let first = self.swap_rounds % 2;
+        self.swap_rounds += 1;
+        for i in (first..self.swap_attempts.len()).step_by(2) {
+            self.swap_attempts[i] += 1;
+            let (low, high) = self.replicas.split_at_mut(i + 1);
+            let a = &mut low[i];
+            let b = &mut high[0];
+            // detailed balance for exchanging configurations between beta_a and beta_b
+            let exponent = (a.beta - b.beta) * (a.energy - b.energy);
+            if exponent >= 0.0 || self.rng.random::<f64>() < exponent.exp() {
+                std::mem::swap(&mut a.state, &mut b.state);
+                std::mem::swap(&mut a.energy, &mut b.energy);
+                self.swap_accepted[i] += 1;
+            }
+        }
+    }
+
+    pub fn run_empty(&mut self) {
+        for i in 0..self.steps {
+            self.step(i);
+        }
+    }
+
+    /// Returns the observations of each position of the ladder
+    pub fn run_with<O: Observer<S> + Default>(&mut self) -> Vec<Vec<O::Observation>> {
+        self.run_with_observer(&O::default())
+    }
+
+    pub fn run_with_observer<O: Observer<S>>(&mut self, observer: &O) -> Vec<Vec<O::Observation>> {
+        let cadence = observer.cadence();
+        let mut measures: Vec<Vec<O::Observation>> = Vec::new();
+        for _ in self.replicas.iter() {
+            measures.push(Vec::new());
+        }
+
+        for i in 0..self.steps {
+            if cadence.measures(i) {
+                for (j, replica) in self.replicas.iter().enumerate() {
+                    measures[j].push(observer.measure_with_energy(
+                        &replica.state,
+                        &replica.params,
+                        replica.energy,
+                    ));
+                }
+            }
+            self.step(i);
+        }
+        measures
+    }
+
+    /// Accepted rate of the metropolis moves of each replica
+    pub fn accepted_rates(&self) -> Vec<f64> {
+        self.replicas.iter().map(|r| r.accepted_rate()).collect()
+    }
+
+    /// Accepted rate of the swaps between replica i and i + 1
+    pub fn swap_acceptance_rates(&self) -> Vec<f64> {
+        self.swap_attempts
+            .iter()
+            .zip(self.swap_accepted.iter())
+            .map(|(&attempts, &accepted)| {
+                if attempts == 0 {
+                    0.0
+                } else {
+                    accepted as f64 / attempts as f64
+                }
+            })
+            .collect()
+    }
+}
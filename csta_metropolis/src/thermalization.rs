@@ -0,0 +1,108 @@
+//! Detection of equilibrium from the energy trace, instead of guessing a burn-in
+
+use rand::Rng;
+
+use crate::{Metropolis, State, acceptance::AcceptanceRule, analysis};
+
+/// Fewest energies a detector compares on each side, shorter stretches are never
+/// declared in equilibrium since their errors can't be estimated
+pub const MIN_ENERGIES: usize = 40;
+
+/// Decides from the energies since the start of the run if the chain is in equilibrium
+pub trait Equilibration {
+    fn equilibrated(&self, energies: &[f64]) -> bool;
+}
+
+/// Equilibrium when the means of the last two windows of energies agree
+/// within `z` standard errors, windows need at least [`MIN_ENERGIES`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlidingWindow {
+    pub window: usize,
+    pub z: f64,
+}
+
+impl Equilibration for SlidingWindow {
+    fn equilibrated(&self, energies: &[f64]) -> bool {
+        if self.window < MIN_ENERGIES || energies.len() < 2 * self.window {
+            return false;
+        }
+        let last = &energies[energies.len() - self.window..];
+        let previous = &energies[energies.len() - 2 * self.window..energies.len() - self.window];
+        z_score(previous, last).is_some_and(|z| z.abs() <= self.z)
+    }
+}
+
+/// Geweke diagnostic: equilibrium when the mean of the first `first` fraction of the
+/// trace agrees within `z` standard errors with the mean of the last `last` fraction,
+/// both fractions need at least [`MIN_ENERGIES`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geweke {
+    pub first: f64,
+    pub last: f64,
+    pub z: f64,
+}
+
+impl Default for Geweke {
+    fn default() -> Self {
+        Self {
+            first: 0.1,
+            last: 0.5,
+            z: 2.0,
+        }
+    }
+}
+
+impl Equilibration for Geweke {
+    fn equilibrated(&self, energies: &[f64]) -> bool {
+        let n = energies.len();
+        let first = (self.first * n as f64) as usize;
+        let last = (self.last * n as f64) as usize;
+        if first < MIN_ENERGIES || last < MIN_ENERGIES || first + last > n {
+            return false;
+        }
+        z_score(&energies[..first], &energies[n - last..]).is_some_and(|z| z.abs() <= self.z)
+    }
+}
+
+/// Difference of the means over its standard error, the errors from batch means
+/// (20 batches) so they take the autocorrelation into account at O(n).
+/// None if a series is too short for the batches.
+fn z_score(a: &[f64], b: &[f64]) -> Option<f64> {
+    let error_a = analysis::blocking_error(a, a.len() / 20)?;
+    let error_b = analysis::blocking_error(b, b.len() / 20)?;
+    let difference = analysis::mean(a) - analysis::mean(b);
+    if difference == 0.0 {
+        return Some(0.0);
+    }
+    Some(difference / (error_a * error_a + error_b * error_b).sqrt())
+}
+
+impl<S: State, R: Rng, A: AcceptanceRule> Metropolis<S, R, A> {
+    /// Steps until `detector` declares equilibrium, checking every `check_every` steps,
+    /// and returns the number of steps it took, or None if it didn't within `max_steps`.
+    /// These steps don't count towards the accepted, invalid or per move counts,
+    /// so observers of any run after this one only see the equilibrated chain.
+    /// The detectors need [`MIN_ENERGIES`] on each side they compare, so with a
+    /// `max_steps` too short for them this runs all the steps and returns None.
+    pub fn thermalize(
+        &mut self,
+        detector: &impl Equilibration,
+        check_every: usize,
+        max_steps: usize,
+    ) -> Option<usize> {
+        self.uncounted(|metropolis| {
+            let mut energies = Vec::new();
+            for i in 1..=max_steps {
+                metropolis.step();
+                energies.push(metropolis.energy);
+                if check_every > 0
+                    && i.is_multiple_of(check_every)
+                    && detector.equilibrated(&energies)
+                {
+                    return Some(i);
+                }
+            }
+            None
+        })
+    }
+}
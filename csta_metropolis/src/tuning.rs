@@ -0,0 +1,86 @@
+//! Tuning of the proposal scale to reach a target acceptance rate
+//!
+//! Adapting the proposal breaks detailed balance, so it is only done during
+//! burn-in, the scale is frozen for the runs afterwards.
+
+use rand::Rng;
+
+use crate::{Metropolis, State, acceptance::AcceptanceRule};
+
+/// A state whose proposals have a scale, like the max displacement of a particle.
+/// propose_change is expected to use it.
+pub trait Tunable: State {
+    fn scale(&self) -> f64;
+    fn set_scale(&mut self, scale: f64);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    /// acceptance rate to reach, 0.5 by default
+    pub target: f64,
+    /// steps between scale updates, 100 by default
+    pub batch: usize,
+    /// number of updates, 50 by default
+    pub batches: usize,
+    /// the scale is kept inside these, (0, inf) by default
+    pub min_scale: f64,
+    pub max_scale: f64,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            target: 0.5,
+            batch: 100,
+            batches: 50,
+            min_scale: f64::MIN_POSITIVE,
+            max_scale: f64::INFINITY,
+        }
+    }
+}
+
+impl Tuning {
+    pub fn with_target(target: f64) -> Self {
+        Self {
+            target,
+            ..Self::default()
+        }
+    }
+}
+
+impl<S: Tunable, R: Rng, A: AcceptanceRule> Metropolis<S, R, A> {
+    /// Runs the batches of the tuning, after the kth one the scale is multiplied by
+    /// exp((rate - target) / (target (1 - target) sqrt(k + 1))), so it grows when too
+    /// many moves are accepted, with steps that shrink so it settles.
+    /// Returns the acceptance rate of the last batch. These steps don't count
+    /// towards the accepted, invalid or per move counts, and the final scale is left in the state.
+    ///
+    /// Panics if the batch is empty, the target is not in (0, 1) or the scale bounds are empty
+    pub fn tune(&mut self, tuning: &Tuning) -> f64 {
+        assert!(tuning.batch > 0, "tuning needs steps in a batch");
+        assert!(
+            tuning.target > 0.0 && tuning.target < 1.0,
+            "target acceptance rate must be in (0, 1)"
+        );
+        assert!(
+            tuning.min_scale > 0.0 && tuning.min_scale <= tuning.max_scale,
+            "scale bounds must be positive and ordered"
+        );
+        self.uncounted(|metropolis| {
+            let mut rate = f64::NAN;
+            for k in 0..tuning.batches {
+                let before = metropolis.accepted_moves;
+                for _ in 0..tuning.batch {
+                    metropolis.step();
+                }
+                rate = (metropolis.accepted_moves - before) as f64 / tuning.batch as f64;
+                let gain = 1.0 / (tuning.target * (1.0 - tuning.target) * ((k + 1) as f64).sqrt());
+                let factor = (gain * (rate - tuning.target)).exp();
+                let scale =
+                    (metropolis.state.scale() * factor).clamp(tuning.min_scale, tuning.max_scale);
+                metropolis.state.set_scale(scale);
+            }
+            rate
+        })
+    }
+}
@@ -0,0 +1,318 @@
+//! Wang-Landau sampling of the density of states g(E)
+//!
+//! Instead of sampling at a fixed beta, the walk is biased by 1/g(E) until the
+//! energy histogram is flat, so a single run gives ln g(E) and from it
+//! canonical averages at any beta.
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::State;
+
+/// Splits the energy axis into the bins in which g(E) is estimated
+pub trait EnergyBins {
+    /// Number of bins
+    fn len(&self) -> usize;
+
+    /// Bin of an energy, None if the energy is out of the sampled range
+    fn bin(&self, energy: f64) -> Option<usize>;
+
+    /// Representative energy of a bin
+    fn energy(&self, bin: usize) -> f64;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// `bins` bins of the same width covering [min, max)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniformBins {
+    pub min: f64,
+    pub max: f64,
+    pub bins: usize,
+}
+
+impl UniformBins {
+    /// Panics if there are no bins, or min and max aren't finite with min < max
+    pub fn new(min: f64, max: f64, bins: usize) -> Self {
+        assert!(bins > 0, "there has to be at least one energy bin");
+        assert!(
+            min.is_finite() && max.is_finite() && min < max,
+            "the energy range has to be finite and not empty, got [{min}, {max})"
+        );
+        Self { min, max, bins }
+    }
+
+    /// Bins centered on each energy of a discrete spectrum, like min, min + spacing, ..., max.
+    /// Panics if the spacing isn't positive and finite, or min and max aren't finite with min <= max
+    pub fn discrete(min: f64, max: f64, spacing: f64) -> Self {
+        assert!(
+            spacing.is_finite() && spacing > 0.0,
+            "the spacing of the energies has to be positive, got {spacing}"
+        );
+        assert!(
+            min.is_finite() && max.is_finite() && min <= max,
+            "the energy range has to be finite, got [{min}, {max}]"
+        );
+        let bins = ((max - min) / spacing).round() as usize + 1;
+        Self {
+            min: min - spacing / 2.0,
+            max: max + spacing / 2.0,
+            bins,
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        (self.max - self.min) / self.bins as f64
+    }
+}
+
+impl EnergyBins for UniformBins {
+    fn len(&self) -> usize {
+        self.bins
+    }
+
+    fn bin(&self, energy: f64) -> Option<usize> {
+        if !(self.min..self.max).contains(&energy) {
+            return None;
+        }
+        let bin = ((energy - self.min) / self.width()) as usize;
+        Some(bin.min(self.bins - 1))
+    }
+
+    fn energy(&self, bin: usize) -> f64 {
+        self.min + (bin as f64 + 0.5) * self.width()
+    }
+}
+
+pub struct WangLandau<S: State, B: EnergyBins, R: Rng> {
+    pub state: S,
+    pub params: S::Params,
+    pub bins: B,
+    pub rng: R,
+    /// the refinement stops once ln f is below this, 1e-8 by default
+    pub ln_f_final: f64,
+    /// a histogram is flat when every visited bin has at least this fraction of the mean, 0.8 by default
+    pub flatness: f64,
+    /// steps between flatness checks, 10_000 by default
+    pub check_every: usize,
+    /// flatness checks at the same ln f before giving up, for walks that never
+    /// reach the binned range or never get flat. 10_000 by default
+    pub max_checks: usize,
+    energy: f64,
+    ln_g: Vec<f64>,
+    histogram: Vec<usize>,
+    visited: Vec<bool>,
+}
+
+impl<S: State, B: EnergyBins, R: Rng> WangLandau<S, B, R> {
+    pub fn new(state: S, mut params: S::Params, bins: B, rng: R) -> Self {
+        let energy = state.energy(&mut params);
+        let len = bins.len();
+        Self {
+            state,
+            params,
+            bins,
+            rng,
+            ln_f_final: 1e-8,
+            flatness: 0.8,
+            check_every: 10_000,
+            max_checks: 10_000,
+            energy,
+            ln_g: vec![0.0; len],
+            histogram: vec![0; len],
+            visited: vec![false; len],
+        }
+    }
+
+    /// One move of the walk, accepted with min(1, g(E) q(x|x') / (g(E') q(x'|x))).
+    /// While the state is outside the binned range every move is accepted,
+    /// moves leaving the range and invalid changes are always rejected.
+    pub fn step(&mut self, ln_f: f64) {
+        let change = self.state.propose_change(&mut self.rng);
+        if self.state.check_change(&change, &self.params).is_ok() {
+            self.try_change(change);
+        }
+
+        if let Some(bin) = self.bins.bin(self.energy) {
+            self.ln_g[bin] += ln_f;
+            self.histogram[bin] += 1;
+            self.visited[bin] = true;
+        }
+    }
+
+    fn try_change(&mut self, change: S::Change) {
+        let ln_q_ratio = self.state.proposal_log_ratio(&change, &self.params);
+        let old_bin = self.bins.bin(self.energy);
+
+        match self.state.delta_energy(&change, &mut self.params) {
+            Some(delta_energy) => {
+                let new_energy = self.energy + delta_energy;
+                if self.accept(old_bin, self.bins.bin(new_energy), ln_q_ratio)
+                    && self.state.try_apply_change(change).is_ok()
+                {
+                    self.energy = new_energy;
+                }
+            }
+            None => {
+                if self.state.try_apply_change(change.clone()).is_err() {
+                    return;
+                }
+                let new_energy = self.state.energy(&mut self.params);
+                if self.accept(old_bin, self.bins.bin(new_energy), ln_q_ratio) {
+                    self.energy = new_energy;
+                } else if let Err(error) = self.state.try_revert_change(change) {
+                    panic!("a rejected change couldn't be reverted, {error}");
+                }
+            }
+        }
+    }
+
+    fn accept(&mut self, old_bin: Option<usize>, new_bin: Option<usize>, ln_q_ratio: f64) -> bool {
+        match (old_bin, new_bin) {
+            (_, None) => old_bin.is_none(),
+            (None, Some(_)) => true,
+            (Some(old), Some(new)) => {
+                let ln_ratio = self.ln_g[old] - self.ln_g[new] + ln_q_ratio;
+                ln_ratio >= 0.0 || self.rng.random::<f64>() < ln_ratio.exp()
+            }
+        }
+    }
+
+    /// Every visited bin has at least `flatness` times the mean of the visited bins
+    pub fn is_flat(&self) -> bool {
+        let visited = self
+            .histogram
+            .iter()
+            .zip(self.visited.iter())
+            .filter(|(_, v)| **v)
+            .map(|(h, _)| *h);
+        let (count, total, min) =
+            visited.fold((0, 0, usize::MAX), |(c, t, m), h| (c + 1, t + h, m.min(h)));
+        if count == 0 {
+            return false;
+        }
+        min as f64 >= self.flatness * total as f64 / count as f64
+    }
+
+    /// Runs the walk, halving ln f each time the histogram gets flat, starting from ln f = 1.
+    /// Gives up if the histogram isn't flat after `max_checks` checks at the same ln f.
+    pub fn run(&mut self) -> Result<DensityOfStates, NotFlat> {
+        let mut ln_f = 1.0;
+        while ln_f > self.ln_f_final {
+            let mut checks = 0;
+            loop {
+                if checks == self.max_checks {
+                    return Err(NotFlat {
+                        ln_f,
+                        partial: self.density_of_states(),
+                    });
+                }
+                for _ in 0..self.check_every {
+                    self.step(ln_f);
+                }
+                checks += 1;
+                if self.is_flat() {
+                    break;
+                }
+            }
+            self.histogram.iter_mut().for_each(|h| *h = 0);
+            ln_f /= 2.0;
+        }
+        Ok(self.density_of_states())
+    }
+
+    /// Current estimate of ln g(E), only for the visited bins
+    pub fn density_of_states(&self) -> DensityOfStates {
+        let (energies, ln_g) = (0..self.bins.len())
+            .filter(|&bin| self.visited[bin])
+            .map(|bin| (self.bins.energy(bin), self.ln_g[bin]))
+            .unzip();
+        DensityOfStates { energies, ln_g }
+    }
+}
+
+/// The histogram didn't get flat within the max number of checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotFlat {
+    /// ln f at which the walk gave up
+    pub ln_f: f64,
+    /// estimate of ln g so far, empty if the walk never reached the binned range
+    pub partial: DensityOfStates,
+}
+
+impl fmt::Display for NotFlat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the energy histogram didn't get flat at ln f = {}, {} bins visited",
+            self.ln_f,
+            self.partial.energies.len()
+        )
+    }
+}
+
+impl std::error::Error for NotFlat {}
+
+/// ln g(E) up to an additive constant, which cancels out in canonical averages
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DensityOfStates {
+    pub energies: Vec<f64>,
+    pub ln_g: Vec<f64>,
+}
+
+impl DensityOfStates {
+    /// Shifts ln g so the total number of states is e^ln_total, like N ln 2 for N ising spins
+    pub fn normalize(&mut self, ln_total: f64) {
+        let shift = ln_total - log_sum_exp(self.ln_g.iter().copied());
+        self.ln_g.iter_mut().for_each(|g| *g += shift);
+    }
+
+    /// ln Z(beta), with the same additive constant as ln g
+    pub fn ln_partition(&self, beta: f64) -> f64 {
+        log_sum_exp(self.exponents(beta))
+    }
+
+    /// `<f(E)>` at beta
+    pub fn canonical_average(&self, beta: f64, f: impl Fn(f64) -> f64) -> f64 {
+        let ln_z = self.ln_partition(beta);
+        self.energies
+            .iter()
+            .zip(self.exponents(beta))
+            .map(|(&e, exponent)| f(e) * (exponent - ln_z).exp())
+            .sum()
+    }
+
+    /// `<E>` at beta
+    pub fn internal_energy(&self, beta: f64) -> f64 {
+        self.canonical_average(beta, |e| e)
+    }
+
+    /// `beta² (<E²> - <E>²)`, in units of k_B
+    pub fn specific_heat(&self, beta: f64) -> f64 {
+        let mean = self.internal_energy(beta);
+        beta * beta * self.canonical_average(beta, |e| (e - mean) * (e - mean))
+    }
+
+    /// F = -ln Z / beta
+    pub fn free_energy(&self, beta: f64) -> f64 {
+        -self.ln_partition(beta) / beta
+    }
+
+    fn exponents(&self, beta: f64) -> impl Iterator<Item = f64> + Clone + '_ {
+        self.energies
+            .iter()
+            .zip(self.ln_g.iter())
+            .map(move |(e, g)| g - beta * e)
+    }
+}
+
+fn log_sum_exp(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values.map(|v| (v - max).exp()).sum::<f64>().ln()
+}
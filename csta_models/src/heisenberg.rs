@@ -0,0 +1,169 @@
+//! Classical Heisenberg model of unit spins, E = -J sum over bonds of s_i · s_j - h sum of s_i,z
+
+use csta_core::{lattice::Graph, vec3::Vec3f64};
+use csta_metropolis::{State, tuning::Tunable};
+use csta_montecarlo::Randomizable;
+use rand::Rng;
+
+use crate::{Model, default_graph};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeisenbergParams {
+    /// coupling, ferromagnetic when positive
+    pub j: f64,
+    /// external field, along z
+    pub h: f64,
+}
+
+impl Default for HeisenbergParams {
+    fn default() -> Self {
+        Self { j: 1.0, h: 0.0 }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heisenberg {
+    pub graph: Graph,
+    pub spins: Vec<Vec3f64>,
+    /// size of the random kick of a proposed change, 2 by default (any direction), see [`Tunable`]
+    pub scale: f64,
+}
+
+/// The spin of the site turns from one direction to another
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeisenbergChange {
+    pub site: usize,
+    pub from: Vec3f64,
+    pub to: Vec3f64,
+}
+
+/// Uniformly distributed in the unit ball
+fn in_unit_ball<R: Rng + ?Sized>(rng: &mut R) -> Vec3f64 {
+    loop {
+        let v = Vec3f64(
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+        );
+        if v.len_squared() <= 1.0 && v.len_squared() > 0.0 {
+            return v;
+        }
+    }
+}
+
+impl Heisenberg {
+    /// Every spin along z
+    pub fn ordered(graph: impl Into<Graph>) -> Self {
+        let graph = graph.into();
+        let spins = vec![Vec3f64(0.0, 0.0, 1.0); graph.len()];
+        Self {
+            graph,
+            spins,
+            scale: 2.0,
+        }
+    }
+
+    /// Every spin pointing anywhere
+    pub fn random<R: Rng + ?Sized>(graph: impl Into<Graph>, rng: &mut R) -> Self {
+        let mut heisenberg = Self::ordered(graph);
+        heisenberg
+            .spins
+            .iter_mut()
+            .for_each(|spin| *spin = in_unit_ball(rng).normalize());
+        heisenberg
+    }
+
+    /// Magnetization per site
+    pub fn magnetization(&self) -> Vec3f64 {
+        let total = self
+            .spins
+            .iter()
+            .fold(Vec3f64::default(), |total, spin| total + spin);
+        total / self.spins.len() as f64
+    }
+
+    fn site_energy(&self, site: usize, spin: &Vec3f64, params: &HeisenbergParams) -> f64 {
+        let bonds: f64 = self
+            .graph
+            .neighbours(site)
+            .iter()
+            .map(|&j| spin.dot(&self.spins[j]))
+            .sum();
+        -params.j * bonds - params.h * spin.z()
+    }
+}
+
+impl Randomizable for Heisenberg {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::random(default_graph(), rng)
+    }
+}
+
+impl State for Heisenberg {
+    type Params = HeisenbergParams;
+    type Change = HeisenbergChange;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.hamiltonian(params)
+    }
+
+    /// The spin plus a kick uniform in a ball of radius scale, normalized.
+    /// Symmetric, as its probability only depends on the angle turned.
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        let site = rng.random_range(0..self.spins.len());
+        let from = self.spins[site];
+        let mut to = (from + in_unit_ball(rng) * self.scale).normalize();
+        if to.len_squared() == 0.0 {
+            to = from;
+        }
+        HeisenbergChange { site, from, to }
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.spins[change.site] = change.to;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.spins[change.site] = change.from;
+    }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        Some(
+            self.site_energy(change.site, &change.to, params)
+                - self.site_energy(change.site, &change.from, params),
+        )
+    }
+}
+
+impl Tunable for Heisenberg {
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+}
+
+impl Model for Heisenberg {
+    fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    fn hamiltonian(&self, params: &Self::Params) -> f64 {
+        let bonds: f64 = self
+            .graph
+            .edges()
+            .map(|(a, b)| self.spins[a].dot(&self.spins[b]))
+            .sum();
+        let field: f64 = self.spins.iter().map(|spin| spin.z()).sum();
+        -params.j * bonds - params.h * field
+    }
+
+    fn order_parameter(&self) -> f64 {
+        self.magnetization().len()
+    }
+}
@@ -0,0 +1,164 @@
+//! Ising model, E = -J sum over bonds of s_i s_j - h sum of s_i, with s = ±1
+
+use csta_core::lattice::Graph;
+use csta_metropolis::{
+    State,
+    cluster::{ClusterError, SpinLattice},
+};
+use csta_montecarlo::Randomizable;
+use rand::Rng;
+
+use crate::{Model, default_graph};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IsingParams {
+    /// coupling, ferromagnetic when positive
+    pub j: f64,
+    /// external field
+    pub h: f64,
+}
+
+impl Default for IsingParams {
+    fn default() -> Self {
+        Self { j: 1.0, h: 0.0 }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ising {
+    pub graph: Graph,
+    pub spins: Vec<i8>,
+}
+
+impl Ising {
+    /// Every spin up
+    pub fn ordered(graph: impl Into<Graph>) -> Self {
+        let graph = graph.into();
+        let spins = vec![1; graph.len()];
+        Self { graph, spins }
+    }
+
+    /// Every spin up or down with the same probability
+    pub fn random<R: Rng + ?Sized>(graph: impl Into<Graph>, rng: &mut R) -> Self {
+        let graph = graph.into();
+        let spins = (0..graph.len())
+            .map(|_| if rng.random() { 1 } else { -1 })
+            .collect();
+        Self { graph, spins }
+    }
+
+    /// Magnetization per site
+    pub fn magnetization(&self) -> f64 {
+        self.spins.iter().map(|&s| f64::from(s)).sum::<f64>() / self.spins.len() as f64
+    }
+
+    fn local_field(&self, site: usize, params: &IsingParams) -> f64 {
+        let neighbours: f64 = self
+            .graph
+            .neighbours(site)
+            .iter()
+            .map(|&j| f64::from(self.spins[j]))
+            .sum();
+        params.j * neighbours + params.h
+    }
+}
+
+impl Randomizable for Ising {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::random(default_graph(), rng)
+    }
+}
+
+impl State for Ising {
+    type Params = IsingParams;
+    type Change = usize;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.hamiltonian(params)
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        rng.random_range(0..self.spins.len())
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.spins[change] *= -1;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.spins[change] *= -1;
+    }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        Some(2.0 * f64::from(self.spins[*change]) * self.local_field(*change, params))
+    }
+}
+
+impl Model for Ising {
+    fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    fn hamiltonian(&self, params: &Self::Params) -> f64 {
+        let bonds: f64 = self
+            .graph
+            .edges()
+            .map(|(a, b)| f64::from(self.spins[a] * self.spins[b]))
+            .sum();
+        let total: f64 = self.spins.iter().map(|&s| f64::from(s)).sum();
+        -params.j * bonds - params.h * total
+    }
+
+    fn order_parameter(&self) -> f64 {
+        self.magnetization()
+    }
+}
+
+/// Cluster updates don't see the field, they only sample the model with h = 0
+impl SpinLattice for Ising {
+    type Spin = i8;
+
+    fn sites(&self) -> usize {
+        self.spins.len()
+    }
+
+    fn neighbours(&self, site: usize) -> impl Iterator<Item = usize> {
+        self.graph.neighbours(site).iter().copied()
+    }
+
+    fn spin(&self, site: usize) -> Self::Spin {
+        self.spins[site]
+    }
+
+    fn set_spin(&mut self, site: usize, spin: Self::Spin) {
+        self.spins[site] = spin;
+    }
+
+    fn bond_energy(&self, _a: usize, _b: usize, params: &Self::Params) -> f64 {
+        2.0 * params.j
+    }
+
+    fn flipped(&self, spin: Self::Spin, _rng: &mut impl Rng) -> Self::Spin {
+        -spin
+    }
+
+    /// The clusters only see ferromagnetic bonds, so j has to be a finite
+    /// non-negative coupling and the field has to be zero
+    fn check_params(&self, params: &Self::Params) -> Result<(), ClusterError> {
+        if !params.j.is_finite() || params.j < 0.0 {
+            Err(ClusterError::UnsupportedParams(format!(
+                "the coupling isn't ferromagnetic, j = {}",
+                params.j
+            )))
+        } else if params.h != 0.0 {
+            Err(ClusterError::UnsupportedParams(format!(
+                "cluster updates ignore the field, h = {}",
+                params.h
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
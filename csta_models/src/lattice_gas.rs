@@ -0,0 +1,124 @@
+//! Lattice gas in the grand canonical ensemble, E = -ε sum over bonds of n_i n_j - μ sum of n_i,
+//! with n = 0 or 1 the occupation of a site.
+//! It maps to the ising model with s = 2n - 1, J = ε/4 and h = (μ + z ε / 2) / 2 for z neighbours.
+
+use csta_core::lattice::Graph;
+use csta_metropolis::State;
+use csta_montecarlo::Randomizable;
+use rand::Rng;
+
+use crate::{Model, default_graph};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatticeGasParams {
+    /// attraction between neighbouring particles when positive
+    pub epsilon: f64,
+    /// chemical potential
+    pub mu: f64,
+}
+
+impl Default for LatticeGasParams {
+    fn default() -> Self {
+        Self {
+            epsilon: 1.0,
+            mu: 0.0,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatticeGas {
+    pub graph: Graph,
+    pub occupied: Vec<bool>,
+}
+
+impl LatticeGas {
+    pub fn empty(graph: impl Into<Graph>) -> Self {
+        let graph = graph.into();
+        let occupied = vec![false; graph.len()];
+        Self { graph, occupied }
+    }
+
+    /// Every site occupied with probability `density`
+    pub fn random<R: Rng + ?Sized>(graph: impl Into<Graph>, density: f64, rng: &mut R) -> Self {
+        let graph = graph.into();
+        let occupied = (0..graph.len()).map(|_| rng.random_bool(density)).collect();
+        Self { graph, occupied }
+    }
+
+    pub fn particles(&self) -> usize {
+        self.occupied.iter().filter(|&&n| n).count()
+    }
+
+    /// Fraction of occupied sites
+    pub fn density(&self) -> f64 {
+        self.particles() as f64 / self.occupied.len() as f64
+    }
+
+    fn occupied_neighbours(&self, site: usize) -> usize {
+        self.graph
+            .neighbours(site)
+            .iter()
+            .filter(|&&j| self.occupied[j])
+            .count()
+    }
+}
+
+impl Randomizable for LatticeGas {
+    /// Half filled on average
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::random(default_graph(), 0.5, rng)
+    }
+}
+
+impl State for LatticeGas {
+    type Params = LatticeGasParams;
+    /// the site whose particle is added or removed
+    type Change = usize;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.hamiltonian(params)
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        rng.random_range(0..self.occupied.len())
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.occupied[change] = !self.occupied[change];
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.occupied[change] = !self.occupied[change];
+    }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        let added = params.epsilon * self.occupied_neighbours(*change) as f64 + params.mu;
+        if self.occupied[*change] {
+            Some(added)
+        } else {
+            Some(-added)
+        }
+    }
+}
+
+impl Model for LatticeGas {
+    fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    fn hamiltonian(&self, params: &Self::Params) -> f64 {
+        let bonds = self
+            .graph
+            .edges()
+            .filter(|&(a, b)| self.occupied[a] && self.occupied[b])
+            .count();
+        -params.epsilon * bonds as f64 - params.mu * self.particles() as f64
+    }
+
+    fn order_parameter(&self) -> f64 {
+        self.density()
+    }
+}
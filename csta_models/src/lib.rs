@@ -0,0 +1,38 @@
+//! Ready-made models on the sites of a graph (usually a lattice)
+//!
+//! Every model implements [`State`] with single site changes and knows its energy
+//! difference, and [`Randomizable`](csta_montecarlo::Randomizable) with a random
+//! configuration on a [`DEFAULT_SIDE`] x [`DEFAULT_SIDE`] periodic square lattice.
+//! Energies count every bond once.
+//!
+//! Particles in continuous space are in [`particles`], with pair potentials
+//! in place of the bonds of a graph.
+
+use csta_core::lattice::{Boundary, Graph, Lattice};
+use csta_metropolis::State;
+
+pub mod heisenberg;
+pub mod ising;
+pub mod lattice_gas;
+pub mod observables;
+pub mod particles;
+pub mod potts;
+pub mod xy;
+
+/// Side of the square lattice of the randomly sampled models
+pub const DEFAULT_SIDE: usize = 16;
+
+/// A model of spins (or occupations) on the sites of a graph
+pub trait Model: State {
+    fn graph(&self) -> &Graph;
+
+    /// Same as [`State::energy`], without needing the params mutably
+    fn hamiltonian(&self, params: &Self::Params) -> f64;
+
+    /// Order parameter per site: m for ising, |m| for vector spins, the density for a gas
+    fn order_parameter(&self) -> f64;
+}
+
+fn default_graph() -> Graph {
+    Lattice::square(DEFAULT_SIDE, DEFAULT_SIDE, Boundary::Periodic).into()
+}
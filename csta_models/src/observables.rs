@@ -0,0 +1,115 @@
+//! Standard observers of the models, and the fluctuation estimators built from their series
+//!
+//! The observers measure at every step, change it with [`Observer::with_cadence`].
+//! Susceptibility and specific heat aren't properties of a single configuration,
+//! they come from the fluctuations of the measured magnetization and energy.
+
+use csta_metropolis::{analysis, observer::Cadence, observer::Observer};
+
+use crate::Model;
+
+/// Total energy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Energy;
+
+/// Energy divided by the number of sites
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EnergyPerSite;
+
+/// Order parameter per site, see [`Model::order_parameter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Magnetization;
+
+/// Absolute value of the order parameter per site, the one to use in finite ising
+/// systems without a field, where m keeps jumping between ±|m|
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AbsMagnetization;
+
+impl<M: Model> Observer<M> for Energy {
+    type Observation = f64;
+
+    fn measure(&self, state: &M, params: &M::Params) -> Self::Observation {
+        state.hamiltonian(params)
+    }
+
+    /// The energy kept by the run, instead of summing the hamiltonian again
+    fn measure_with_energy(
+        &self,
+        _state: &M,
+        _params: &M::Params,
+        energy: f64,
+    ) -> Self::Observation {
+        energy
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 0)
+    }
+}
+
+impl<M: Model> Observer<M> for EnergyPerSite {
+    type Observation = f64;
+
+    fn measure(&self, state: &M, params: &M::Params) -> Self::Observation {
+        state.hamiltonian(params) / state.graph().len() as f64
+    }
+
+    fn measure_with_energy(
+        &self,
+        state: &M,
+        _params: &M::Params,
+        energy: f64,
+    ) -> Self::Observation {
+        energy / state.graph().len() as f64
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 0)
+    }
+}
+
+impl<M: Model> Observer<M> for Magnetization {
+    type Observation = f64;
+
+    fn measure(&self, state: &M, _params: &M::Params) -> Self::Observation {
+        state.order_parameter()
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 0)
+    }
+}
+
+impl<M: Model> Observer<M> for AbsMagnetization {
+    type Observation = f64;
+
+    fn measure(&self, state: &M, _params: &M::Params) -> Self::Observation {
+        state.order_parameter().abs()
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 0)
+    }
+}
+
+/// χ = beta N (`<m²> - <m>²`), from the magnetizations per site of `sites` sites
+pub fn susceptibility(beta: f64, sites: usize, magnetizations: &[f64]) -> f64 {
+    beta * sites as f64 * population_variance(magnetizations)
+}
+
+/// C = beta² (`<E²> - <E>²`) / N, per site, from the total energies of `sites` sites
+pub fn specific_heat(beta: f64, sites: usize, energies: &[f64]) -> f64 {
+    beta * beta * population_variance(energies) / sites as f64
+}
+
+/// `U = 1 - <m⁴> / (3 <m²>²)`, whose curves for different sizes cross at the critical point
+pub fn binder_cumulant(magnetizations: &[f64]) -> f64 {
+    let m2 = analysis::mean(&magnetizations.iter().map(|m| m.powi(2)).collect::<Vec<_>>());
+    let m4 = analysis::mean(&magnetizations.iter().map(|m| m.powi(4)).collect::<Vec<_>>());
+    1.0 - m4 / (3.0 * m2 * m2)
+}
+
+fn population_variance(data: &[f64]) -> f64 {
+    let mean = analysis::mean(data);
+    data.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / data.len() as f64
+}
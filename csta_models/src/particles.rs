@@ -0,0 +1,560 @@
+//! Particles in a periodic box, interacting through a pair potential, E = sum over pairs of u(r_ij)
+//!
+//! Distances follow the minimum image convention, so the cutoff of the potential
+//! should be at most half the smallest side of the box. The potential is the
+//! [`State::Params`], so it can be changed between runs like any coupling.
+//! A change displaces a single particle and its energy difference is O(N).
+//!
+//! Potentials with a hard core forbid the configurations with two particles closer
+//! than it, such changes are rejected by [`State::check_change`] and counted as
+//! invalid moves. The energy of a configuration with overlaps is infinite.
+//!
+//! With a [`CellList`] at least as wide as the cutoff, see [`Particles::with_cell_list`],
+//! a change only looks at the particles of the adjacent cells, O(1) at a fixed density.
+//!
+//! They are a [`ParticleSystem`], to be sampled in the grand canonical and
+//! isobaric ensembles of [`csta_metropolis::ensembles`].
+
+use std::marker::PhantomData;
+
+use csta_core::{neighbours::CellList, vec3::Vec3f64};
+use csta_metropolis::{ModificationError, State, ensembles::ParticleSystem, tuning::Tunable};
+use csta_montecarlo::Randomizable;
+use rand::Rng;
+
+/// Particles of the randomly sampled systems
+pub const DEFAULT_PARTICLES: usize = 64;
+
+/// Number density of the randomly sampled systems
+pub const DEFAULT_DENSITY: f64 = 0.5;
+
+/// Energy of two particles as a function of their distance
+pub trait PairPotential {
+    /// Energy at distance r, only asked for r below the cutoff and above the core
+    fn energy(&self, r: f64) -> f64;
+
+    /// Distance from which the energy is zero, infinite by default
+    fn cutoff(&self) -> f64 {
+        f64::INFINITY
+    }
+
+    /// Distance below which particles can't be, zero by default
+    fn core(&self) -> f64 {
+        0.0
+    }
+
+    /// Energy at any distance: zero past the cutoff, infinite inside the core
+    fn pair_energy(&self, r: f64) -> f64 {
+        if r < self.core() {
+            f64::INFINITY
+        } else if r >= self.cutoff() {
+            0.0
+        } else {
+            self.energy(r)
+        }
+    }
+}
+
+/// 4ε ((σ/r)¹² - (σ/r)⁶), truncated at the cutoff and shifted to be continuous there
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LennardJones {
+    /// depth of the well
+    pub epsilon: f64,
+    /// distance where the unshifted energy is zero
+    pub sigma: f64,
+    pub cutoff: f64,
+}
+
+impl LennardJones {
+    /// Cut at the usual 2.5σ
+    pub fn new(epsilon: f64, sigma: f64) -> Self {
+        Self {
+            epsilon,
+            sigma,
+            cutoff: 2.5 * sigma,
+        }
+    }
+
+    fn unshifted(&self, r: f64) -> f64 {
+        let s6 = (self.sigma / r).powi(6);
+        4.0 * self.epsilon * (s6 * s6 - s6)
+    }
+}
+
+impl Default for LennardJones {
+    /// Reduced units, ε = σ = 1
+    fn default() -> Self {
+        Self::new(1.0, 1.0)
+    }
+}
+
+impl PairPotential for LennardJones {
+    fn energy(&self, r: f64) -> f64 {
+        if self.cutoff.is_finite() {
+            self.unshifted(r) - self.unshifted(self.cutoff)
+        } else {
+            self.unshifted(r)
+        }
+    }
+
+    fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+}
+
+/// Impenetrable spheres, with no energy unless they overlap
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HardSphere {
+    pub diameter: f64,
+}
+
+impl Default for HardSphere {
+    fn default() -> Self {
+        Self { diameter: 1.0 }
+    }
+}
+
+impl PairPotential for HardSphere {
+    fn energy(&self, _r: f64) -> f64 {
+        0.0
+    }
+
+    fn cutoff(&self) -> f64 {
+        self.diameter
+    }
+
+    fn core(&self) -> f64 {
+        self.diameter
+    }
+}
+
+/// Screened coulomb, ε exp(-κ r) / r, truncated at the cutoff and shifted
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Yukawa {
+    /// strength, repulsive when positive
+    pub epsilon: f64,
+    /// inverse screening length
+    pub kappa: f64,
+    pub cutoff: f64,
+}
+
+impl Default for Yukawa {
+    fn default() -> Self {
+        Self {
+            epsilon: 1.0,
+            kappa: 1.0,
+            cutoff: 3.0,
+        }
+    }
+}
+
+impl PairPotential for Yukawa {
+    fn energy(&self, r: f64) -> f64 {
+        let u = |r: f64| self.epsilon * (-self.kappa * r).exp() / r;
+        u(r) - u(self.cutoff)
+    }
+
+    fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+}
+
+/// Like charges, k q² / r, truncated at the cutoff and shifted.
+/// Without a neutralizing background or an Ewald sum it's only a rough model of long range forces.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coulomb {
+    /// k q², repulsive when positive
+    pub strength: f64,
+    pub cutoff: f64,
+}
+
+impl Default for Coulomb {
+    fn default() -> Self {
+        Self {
+            strength: 1.0,
+            cutoff: 3.0,
+        }
+    }
+}
+
+impl PairPotential for Coulomb {
+    fn energy(&self, r: f64) -> f64 {
+        self.strength * (1.0 / r - 1.0 / self.cutoff)
+    }
+
+    fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Particles<P> {
+    /// positions, inside the box, [0, side) in every axis.
+    /// The cell list has to be rebuilt after moving them by hand.
+    pub positions: Vec<Vec3f64>,
+    /// sides of the periodic box
+    pub side: Vec3f64,
+    /// max displacement along each axis of a proposed change, 0.5 by default, see [`Tunable`]
+    pub scale: f64,
+    cells: Option<CellList<Vec3f64>>,
+    potential: PhantomData<fn() -> P>,
+}
+
+/// The particle moves from one position to another
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleChange {
+    pub index: usize,
+    pub from: Vec3f64,
+    pub to: Vec3f64,
+}
+
+impl<P> Particles<P> {
+    /// Particles at the given positions, wrapped into the box
+    pub fn new(positions: Vec<Vec3f64>, side: Vec3f64) -> Self {
+        let mut particles = Self {
+            positions,
+            side,
+            scale: 0.5,
+            cells: None,
+            potential: PhantomData,
+        };
+        particles.positions = particles
+            .positions
+            .iter()
+            .map(|&p| particles.wrap(p))
+            .collect();
+        particles
+    }
+
+    /// n particles on a simple cubic grid of a cubic box, as far from each other as it allows
+    pub fn grid(n: usize, side: f64) -> Self {
+        let per_side = (n as f64).cbrt().ceil() as usize;
+        let spacing = side / per_side as f64;
+        let positions = (0..n)
+            .map(|i| {
+                let (x, y, z) = (
+                    i % per_side,
+                    i / per_side % per_side,
+                    i / per_side / per_side,
+                );
+                Vec3f64(x as f64, y as f64, z as f64) * spacing
+            })
+            .collect();
+        Self::new(positions, Vec3f64(side, side, side))
+    }
+
+    /// n particles anywhere in a cubic box, they may overlap
+    pub fn random<R: Rng + ?Sized>(n: usize, side: f64, rng: &mut R) -> Self {
+        let positions = (0..n)
+            .map(|_| {
+                Vec3f64(
+                    rng.random_range(0.0..side),
+                    rng.random_range(0.0..side),
+                    rng.random_range(0.0..side),
+                )
+            })
+            .collect();
+        Self::new(positions, Vec3f64(side, side, side))
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.side.0 * self.side.1 * self.side.2
+    }
+
+    /// Particles per unit volume
+    pub fn density(&self) -> f64 {
+        self.len() as f64 / self.volume()
+    }
+
+    /// Same position, inside the box
+    pub fn wrap(&self, position: Vec3f64) -> Vec3f64 {
+        Vec3f64(
+            position.0.rem_euclid(self.side.0),
+            position.1.rem_euclid(self.side.1),
+            position.2.rem_euclid(self.side.2),
+        )
+    }
+
+    /// Shortest of the periodic images of the separation
+    pub fn minimum_image(&self, separation: Vec3f64) -> Vec3f64 {
+        let image = |d: f64, side: f64| d - side * (d / side).round();
+        Vec3f64(
+            image(separation.0, self.side.0),
+            image(separation.1, self.side.1),
+            image(separation.2, self.side.2),
+        )
+    }
+
+    /// Minimum image distance between two positions
+    pub fn distance(&self, a: Vec3f64, b: Vec3f64) -> f64 {
+        self.minimum_image(a - b).len()
+    }
+
+    /// Same particles, sorted in a cell list with cells at least `cutoff` wide.
+    /// It's only used for potentials whose cutoff (and core) is below it.
+    pub fn with_cell_list(mut self, cutoff: f64) -> Self {
+        self.cells = Some(CellList::new(self.side, cutoff, &self.positions));
+        self
+    }
+
+    pub fn cell_list(&self) -> Option<&CellList<Vec3f64>> {
+        self.cells.as_ref()
+    }
+
+    /// Sorts the particles in the cell list again, after moving them by hand
+    pub fn rebuild_cells(&mut self) {
+        if let Some(cells) = &mut self.cells {
+            cells.rebuild(&self.positions);
+        }
+    }
+
+    // particles that may be within `reach` of the position, every one without a cell list
+    fn near(&self, position: Vec3f64, reach: f64) -> Box<dyn Iterator<Item = usize> + '_> {
+        match &self.cells {
+            Some(cells) if reach <= cells.cutoff() => Box::new(cells.candidates(position)),
+            _ => Box::new(0..self.positions.len()),
+        }
+    }
+}
+
+impl<P: PairPotential> Particles<P> {
+    /// Same as [`State::energy`], without needing the potential mutably
+    pub fn hamiltonian(&self, potential: &P) -> f64 {
+        let mut energy = 0.0;
+        for (i, &a) in self.positions.iter().enumerate() {
+            for j in self.near(a, potential.cutoff()).filter(|&j| j > i) {
+                energy += potential.pair_energy(self.distance(a, self.positions[j]));
+            }
+        }
+        energy
+    }
+
+    /// Energy of a particle at `position` with every other particle but `index`
+    pub fn particle_energy(&self, index: usize, position: Vec3f64, potential: &P) -> f64 {
+        self.near(position, potential.cutoff())
+            .filter(|&j| j != index)
+            .map(|j| potential.pair_energy(self.distance(position, self.positions[j])))
+            .sum()
+    }
+
+    /// A particle but `except` closer than `core` to the position
+    fn overlapping(&self, position: Vec3f64, except: Option<usize>, core: f64) -> Option<usize> {
+        if core <= 0.0 {
+            return None;
+        }
+        self.near(position, core)
+            .find(|&j| Some(j) != except && self.distance(position, self.positions[j]) < core)
+    }
+
+    /// Whether any two particles are closer than the core of the potential
+    pub fn overlaps(&self, potential: &P) -> bool {
+        let core = potential.core();
+        core > 0.0
+            && self.positions.iter().enumerate().any(|(i, &a)| {
+                self.near(a, core)
+                    .any(|j| j > i && self.distance(a, self.positions[j]) < core)
+            })
+    }
+}
+
+impl<P> Randomizable for Particles<P> {
+    /// [`DEFAULT_PARTICLES`] anywhere in a cubic box at [`DEFAULT_DENSITY`]
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let side = (DEFAULT_PARTICLES as f64 / DEFAULT_DENSITY).cbrt();
+        Self::random(DEFAULT_PARTICLES, side, rng)
+    }
+}
+
+impl<P: PairPotential> State for Particles<P> {
+    type Params = P;
+    type Change = ParticleChange;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.hamiltonian(params)
+    }
+
+    /// Without particles the change moves a missing particle 0, which check_change rejects
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        if self.positions.is_empty() {
+            return ParticleChange {
+                index: 0,
+                from: Vec3f64::default(),
+                to: Vec3f64::default(),
+            };
+        }
+        let index = rng.random_range(0..self.positions.len());
+        let from = self.positions[index];
+        let kick = Vec3f64(
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+            rng.random_range(-1.0..1.0),
+        );
+        let to = self.wrap(from + kick * self.scale);
+        ParticleChange { index, from, to }
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.positions[change.index] = change.to;
+        if let Some(cells) = &mut self.cells {
+            cells.move_particle(change.index, change.to);
+        }
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.positions[change.index] = change.from;
+        if let Some(cells) = &mut self.cells {
+            cells.move_particle(change.index, change.from);
+        }
+    }
+
+    fn check_change(
+        &self,
+        change: &Self::Change,
+        params: &Self::Params,
+    ) -> Result<(), ModificationError> {
+        if change.index >= self.positions.len() {
+            return Err(ModificationError::new("there's no particle to move"));
+        }
+        match self.overlapping(change.to, Some(change.index), params.core()) {
+            Some(other) => Err(ModificationError::new(format!(
+                "particle {} would overlap particle {other}",
+                change.index
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        Some(
+            self.particle_energy(change.index, change.to, params)
+                - self.particle_energy(change.index, change.from, params),
+        )
+    }
+}
+
+impl<P: PairPotential> Tunable for Particles<P> {
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+}
+
+impl<P: PairPotential> ParticleSystem for Particles<P> {
+    type Position = Vec3f64;
+
+    fn particles(&self) -> usize {
+        self.len()
+    }
+
+    fn volume(&self) -> f64 {
+        Particles::volume(self)
+    }
+
+    fn random_position(&self, rng: &mut impl Rng) -> Self::Position {
+        Vec3f64(
+            rng.random_range(0.0..self.side.0),
+            rng.random_range(0.0..self.side.1),
+            rng.random_range(0.0..self.side.2),
+        )
+    }
+
+    fn position(&self, index: usize) -> Self::Position {
+        self.positions[index]
+    }
+
+    fn interaction_energy(
+        &self,
+        position: &Self::Position,
+        except: Option<usize>,
+        params: &mut Self::Params,
+    ) -> f64 {
+        self.particle_energy(except.unwrap_or(self.len()), *position, params)
+    }
+
+    fn insert(&mut self, position: Self::Position) {
+        let position = self.wrap(position);
+        self.positions.push(position);
+        if let Some(cells) = &mut self.cells {
+            cells.insert(position);
+        }
+    }
+
+    fn remove(&mut self, index: usize) -> Self::Position {
+        if let Some(cells) = &mut self.cells {
+            cells.remove(index);
+        }
+        self.positions.swap_remove(index)
+    }
+
+    fn check_position(
+        &self,
+        position: &Self::Position,
+        except: Option<usize>,
+        params: &Self::Params,
+    ) -> Result<(), ModificationError> {
+        match self.overlapping(*position, except, params.core()) {
+            Some(other) => Err(ModificationError::new(format!(
+                "a particle there would overlap particle {other}"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Distances scale with the sides, so only a shrinking box can make particles overlap,
+    /// or leave the finite cutoff of the potential or of the cell list past half a side
+    fn check_scale(&self, factor: f64, params: &Self::Params) -> Result<(), ModificationError> {
+        let stretch = factor.cbrt();
+        if stretch >= 1.0 {
+            return Ok(());
+        }
+        let half_side = 0.5 * stretch * self.side.0.min(self.side.1).min(self.side.2);
+        let cutoff = self
+            .cells
+            .as_ref()
+            .map_or(params.cutoff(), |cells| cells.cutoff().max(params.cutoff()));
+        if cutoff.is_finite() && cutoff > half_side {
+            return Err(ModificationError::new(format!(
+                "the cutoff {cutoff} would be more than half the side of the smaller box"
+            )));
+        }
+        // closer than this now, closer than the core after the scaling
+        let reach = params.core() / stretch;
+        for (i, &a) in self.positions.iter().enumerate() {
+            if let Some(j) = self.overlapping(a, Some(i), reach) {
+                return Err(ModificationError::new(format!(
+                    "particles {i} and {j} would overlap in the smaller box"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Every side is scaled by the cube root of the factor, the cells are built again
+    /// with the same cutoff, which [`check_scale`](ParticleSystem::check_scale)
+    /// keeps within half a side
+    fn scale_volume(&mut self, factor: f64) {
+        let stretch = factor.cbrt();
+        self.side *= stretch;
+        self.positions.iter_mut().for_each(|p| *p *= stretch);
+        if let Some(cells) = &self.cells {
+            self.cells = Some(CellList::new(self.side, cells.cutoff(), &self.positions));
+        }
+    }
+}
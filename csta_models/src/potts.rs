@@ -0,0 +1,190 @@
+//! q-state Potts model, E = -J sum over bonds of δ(s_i, s_j), with s in 0..q
+
+use csta_core::lattice::Graph;
+use csta_metropolis::{
+    State,
+    cluster::{ClusterError, SpinLattice},
+};
+use csta_montecarlo::Randomizable;
+use rand::Rng;
+
+use crate::{Model, default_graph};
+
+/// Number of states of the randomly sampled potts models
+pub const DEFAULT_Q: usize = 3;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PottsParams {
+    /// coupling, ferromagnetic when positive
+    pub j: f64,
+}
+
+impl Default for PottsParams {
+    fn default() -> Self {
+        Self { j: 1.0 }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Potts {
+    /// number of states, at least 2
+    pub q: usize,
+    pub graph: Graph,
+    pub spins: Vec<usize>,
+}
+
+/// The site changes from one state to another
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PottsChange {
+    pub site: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Potts {
+    /// Every spin in state 0
+    pub fn ordered(q: usize, graph: impl Into<Graph>) -> Self {
+        let graph = graph.into();
+        let spins = vec![0; graph.len()];
+        Self { q, graph, spins }
+    }
+
+    /// Every spin in any of the q states with the same probability
+    pub fn random<R: Rng + ?Sized>(q: usize, graph: impl Into<Graph>, rng: &mut R) -> Self {
+        let graph = graph.into();
+        let spins = (0..graph.len()).map(|_| rng.random_range(0..q)).collect();
+        Self { q, graph, spins }
+    }
+
+    /// Fraction of the spins in each state
+    pub fn fractions(&self) -> Vec<f64> {
+        let mut counts = vec![0usize; self.q];
+        self.spins.iter().for_each(|&s| counts[s] += 1);
+        counts
+            .into_iter()
+            .map(|c| c as f64 / self.spins.len() as f64)
+            .collect()
+    }
+
+    /// Random state other than `spin`, every other one with the same probability
+    fn other<R: Rng + ?Sized>(&self, spin: usize, rng: &mut R) -> usize {
+        (spin + rng.random_range(1..self.q)) % self.q
+    }
+
+    fn aligned_neighbours(&self, site: usize, spin: usize) -> usize {
+        self.graph
+            .neighbours(site)
+            .iter()
+            .filter(|&&j| self.spins[j] == spin)
+            .count()
+    }
+}
+
+impl Randomizable for Potts {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::random(DEFAULT_Q, default_graph(), rng)
+    }
+}
+
+impl State for Potts {
+    type Params = PottsParams;
+    type Change = PottsChange;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.hamiltonian(params)
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        let site = rng.random_range(0..self.spins.len());
+        let from = self.spins[site];
+        PottsChange {
+            site,
+            from,
+            to: self.other(from, rng),
+        }
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.spins[change.site] = change.to;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.spins[change.site] = change.from;
+    }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        let before = self.aligned_neighbours(change.site, change.from) as f64;
+        let after = self.aligned_neighbours(change.site, change.to) as f64;
+        Some(-params.j * (after - before))
+    }
+}
+
+impl Model for Potts {
+    fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    fn hamiltonian(&self, params: &Self::Params) -> f64 {
+        let aligned = self
+            .graph
+            .edges()
+            .filter(|&(a, b)| self.spins[a] == self.spins[b])
+            .count();
+        -params.j * aligned as f64
+    }
+
+    /// (q max fraction - 1) / (q - 1), 1 when ordered and 0 when every state is equally populated
+    fn order_parameter(&self) -> f64 {
+        let max = self.fractions().into_iter().fold(0.0, f64::max);
+        (self.q as f64 * max - 1.0) / (self.q as f64 - 1.0)
+    }
+}
+
+impl SpinLattice for Potts {
+    type Spin = usize;
+
+    fn sites(&self) -> usize {
+        self.spins.len()
+    }
+
+    fn neighbours(&self, site: usize) -> impl Iterator<Item = usize> {
+        self.graph.neighbours(site).iter().copied()
+    }
+
+    fn spin(&self, site: usize) -> Self::Spin {
+        self.spins[site]
+    }
+
+    fn set_spin(&mut self, site: usize, spin: Self::Spin) {
+        self.spins[site] = spin;
+    }
+
+    fn bond_energy(&self, _a: usize, _b: usize, params: &Self::Params) -> f64 {
+        params.j
+    }
+
+    fn flipped(&self, spin: Self::Spin, rng: &mut impl Rng) -> Self::Spin {
+        self.other(spin, rng)
+    }
+
+    /// The clusters only see ferromagnetic bonds, so j has to be a finite
+    /// non-negative coupling, and a flip needs at least 2 states
+    fn check_params(&self, params: &Self::Params) -> Result<(), ClusterError> {
+        if self.q < 2 {
+            Err(ClusterError::UnsupportedParams(format!(
+                "a spin can't be flipped with q = {}",
+                self.q
+            )))
+        } else if !params.j.is_finite() || params.j < 0.0 {
+            Err(ClusterError::UnsupportedParams(format!(
+                "the coupling isn't ferromagnetic, j = {}",
+                params.j
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! XY model of planar spins, E = -J sum over bonds of cos(θ_i - θ_j) - h sum of cos θ_i
+
+use std::f64::consts::TAU;
+
+use csta_core::lattice::Graph;
+use csta_metropolis::{State, tuning::Tunable};
+use csta_montecarlo::Randomizable;
+use rand::Rng;
+
+use crate::{Model, default_graph};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XyParams {
+    /// coupling, ferromagnetic when positive
+    pub j: f64,
+    /// external field, along θ = 0
+    pub h: f64,
+}
+
+impl Default for XyParams {
+    fn default() -> Self {
+        Self { j: 1.0, h: 0.0 }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Xy {
+    pub graph: Graph,
+    /// angle of each spin, in [0, 2π)
+    pub angles: Vec<f64>,
+    /// max rotation of a proposed change, π by default (any angle), see [`Tunable`]
+    pub scale: f64,
+}
+
+/// The spin of the site rotates from one angle to another
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XyChange {
+    pub site: usize,
+    pub from: f64,
+    pub to: f64,
+}
+
+impl Xy {
+    /// Every spin along θ = 0
+    pub fn ordered(graph: impl Into<Graph>) -> Self {
+        let graph = graph.into();
+        let angles = vec![0.0; graph.len()];
+        Self {
+            graph,
+            angles,
+            scale: std::f64::consts::PI,
+        }
+    }
+
+    /// Every spin pointing anywhere
+    pub fn random<R: Rng + ?Sized>(graph: impl Into<Graph>, rng: &mut R) -> Self {
+        let mut xy = Self::ordered(graph);
+        xy.angles
+            .iter_mut()
+            .for_each(|angle| *angle = rng.random_range(0.0..TAU));
+        xy
+    }
+
+    /// Magnetization per site, (mx, my)
+    pub fn magnetization(&self) -> (f64, f64) {
+        let n = self.angles.len() as f64;
+        let (x, y) = self.angles.iter().fold((0.0, 0.0), |(x, y), angle| {
+            (x + angle.cos(), y + angle.sin())
+        });
+        (x / n, y / n)
+    }
+
+    fn site_energy(&self, site: usize, angle: f64, params: &XyParams) -> f64 {
+        let bonds: f64 = self
+            .graph
+            .neighbours(site)
+            .iter()
+            .map(|&j| (angle - self.angles[j]).cos())
+            .sum();
+        -params.j * bonds - params.h * angle.cos()
+    }
+}
+
+impl Randomizable for Xy {
+    fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::random(default_graph(), rng)
+    }
+}
+
+impl State for Xy {
+    type Params = XyParams;
+    type Change = XyChange;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.hamiltonian(params)
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        let site = rng.random_range(0..self.angles.len());
+        let from = self.angles[site];
+        let to = (from + self.scale * rng.random_range(-1.0..1.0)).rem_euclid(TAU);
+        XyChange { site, from, to }
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.angles[change.site] = change.to;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.angles[change.site] = change.from;
+    }
+
+    fn delta_energy(&self, change: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        Some(
+            self.site_energy(change.site, change.to, params)
+                - self.site_energy(change.site, change.from, params),
+        )
+    }
+}
+
+impl Tunable for Xy {
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+}
+
+impl Model for Xy {
+    fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    fn hamiltonian(&self, params: &Self::Params) -> f64 {
+        let bonds: f64 = self
+            .graph
+            .edges()
+            .map(|(a, b)| (self.angles[a] - self.angles[b]).cos())
+            .sum();
+        let field: f64 = self.angles.iter().map(|angle| angle.cos()).sum();
+        -params.j * bonds - params.h * field
+    }
+
+    fn order_parameter(&self) -> f64 {
+        let (x, y) = self.magnetization();
+        x.hypot(y)
+    }
+}
@@ -14,6 +14,10 @@ use csta_core::{
 ///  
 use rand::{Rng, rngs::ThreadRng};
 
+use crate::seed::SeedRng;
+
+pub mod seed;
+
 pub trait Randomizable {
     fn sample<R: Rng + ?Sized>(rng: &mut R) -> Self;
 }
@@ -105,6 +109,22 @@ impl<T: Randomizable, R: Rng> MonteCarlo<T, R> {
     }
 }
 
+impl<T: Randomizable> MonteCarlo<T, SeedRng> {
+    /// All samples come from stream 0 of the seed, the rng of [`seed::seeded`]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::new(seed::seeded(seed))
+    }
+
+    /// Every sample comes from its own stream of the seed, see [`MonteCarloStreams`]
+    pub fn streams(seed: u64) -> MonteCarloStreams<T> {
+        MonteCarloStreams {
+            seed,
+            index: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<T: Randomizable, R: Rng> Iterator for MonteCarlo<T, R> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
@@ -120,3 +140,52 @@ where
         Self::new(rand::rng())
     }
 }
+
+/// Iterator of samples where the nth sample is taken from the nth stream of the seed,
+/// and is yielded along with that stream so its simulation can keep using it.
+/// The nth sample is always the same, no matter how many samples were taken before.
+#[derive(Debug)]
+pub struct MonteCarloStreams<T: Randomizable> {
+    seed: u64,
+    index: u64,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Randomizable> MonteCarloStreams<T> {
+    /// The nth sample and its stream
+    pub fn get(&self, index: u64) -> (T, SeedRng) {
+        let mut rng = seed::stream(self.seed, index);
+        (T::sample(&mut rng), rng)
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T: Randomizable + Send> MonteCarloStreams<T> {
+    /// Samples of the range of streams taken in parallel, in the order of the range.
+    /// They are the same samples as the ones given by get.
+    pub fn par_get(&self, range: std::ops::Range<u64>) -> Vec<(T, SeedRng)> {
+        use rayon::prelude::*;
+        let seed = self.seed;
+        range
+            .into_par_iter()
+            .map(|index| {
+                let mut rng = seed::stream(seed, index);
+                (T::sample(&mut rng), rng)
+            })
+            .collect()
+    }
+}
+
+impl<T: Randomizable> Iterator for MonteCarloStreams<T> {
+    type Item = (T, SeedRng);
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.get(self.index);
+        self.index += 1;
+        Some(item)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.index += n as u64;
+        self.next()
+    }
+}
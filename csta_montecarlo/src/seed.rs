@@ -0,0 +1,30 @@
+//! Seeded rngs, so runs can be reproduced bit by bit
+//!
+//! A seed gives a family of independent streams, meant to be used one per
+//! replica, chain or montecarlo sample, so the result of each one doesn't
+//! depend on how many others are run or in which order.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Rng used by everything that takes a `u64` seed
+pub type SeedRng = ChaCha8Rng;
+
+/// Rng for a seed, the same as its stream 0, and as the rng of `MonteCarlo::with_seed(seed)`
+/// and of `MetropolisBuilder::seed(seed)`. Runs that need independent rngs of the same
+/// seed should all take them from [`stream`] or [`streams`] instead.
+pub fn seeded(seed: u64) -> SeedRng {
+    SeedRng::seed_from_u64(seed)
+}
+
+/// The nth independent stream of a seed
+pub fn stream(seed: u64, stream: u64) -> SeedRng {
+    let mut rng = seeded(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+/// Streams 0, 1, 2, ... of a seed, the first one being [`seeded`]
+pub fn streams(seed: u64) -> impl Iterator<Item = SeedRng> {
+    (0..).map(move |i| stream(seed, i))
+}
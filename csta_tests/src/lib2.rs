@@ -5,7 +5,12 @@ fn main() {
     MonteCarlo::<Something, _>::default()
         .take(10)
         .for_each(|s| {
-            let mut metropoli = Metropolis::with_state(s, 1.5, 1000);
+            let mut metropoli = Metropolis::builder()
+                .state(s)
+                .beta(1.5)
+                .steps(1000)
+                .build()
+                .unwrap();
             metropoli.run_empty();
         });
 }
@@ -67,9 +72,13 @@ impl State for Algo {
 
 impl Randomizable for Algo {
     fn sample<R: rand::Rng + ?Sized>(_rng: &mut R) -> Self {
-        let mut rng = rand::rng();
         Algo {
-            metropolis: Metropolis::with_all(Something::sample(&mut rng), (), 1.5, 1_000, rng),
+            metropolis: Metropolis::builder()
+                .sample_state()
+                .beta(1.5)
+                .steps(1_000)
+                .build()
+                .unwrap(),
         }
     }
 }
@@ -77,7 +86,12 @@ impl Randomizable for Algo {
 fn using_algo() {
     MonteCarlo::<Algo, _>::default().take(10).for_each(|algo| {
         let beta = algo.metropolis.beta;
-        let mut metropolis = Metropolis::with_state(algo, beta, 100);
+        let mut metropolis = Metropolis::builder()
+            .state(algo)
+            .beta(beta)
+            .steps(100)
+            .build()
+            .unwrap();
         metropolis.run_empty();
     });
 }
@@ -0,0 +1,113 @@
+//!
+//! Every acceptance rule samples the Boltzmann weights of a two level system,
+//! greedy only goes down, and infinite or NaN energies are never accepted
+//!
+mod common;
+
+use common::{Energy, Ring, RingParams};
+use csta::prelude::*;
+
+/// A level at 0 and one at the gap given as params
+#[derive(Debug, Clone, PartialEq)]
+struct TwoLevel {
+    excited: bool,
+}
+
+impl State for TwoLevel {
+    type Params = f64;
+    type Change = ();
+
+    fn energy(&self, gap: &mut Self::Params) -> f64 {
+        if self.excited { *gap } else { 0.0 }
+    }
+
+    fn propose_change(&self, _rng: &mut impl rand::Rng) -> Self::Change {}
+
+    fn apply_change(&mut self, _change: Self::Change) {
+        self.excited = !self.excited;
+    }
+
+    fn revert_change(&mut self, _change: Self::Change) {
+        self.excited = !self.excited;
+    }
+}
+
+#[derive(Default)]
+struct Excited;
+
+impl Observer<TwoLevel> for Excited {
+    type Observation = f64;
+
+    fn measure(&self, state: &TwoLevel, _params: &f64) -> Self::Observation {
+        if state.excited { 1.0 } else { 0.0 }
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 1_000)
+    }
+}
+
+fn excited_fraction<A: AcceptanceRule>(rule: A, beta: f64, gap: f64) -> f64 {
+    let (_, (excited,)) = MetropolisBuilder::with_params(gap)
+        .state(TwoLevel { excited: false })
+        .beta(beta)
+        .steps(200_000)
+        .seed(7)
+        .rule(rule)
+        .observers((Excited,))
+        .run()
+        .unwrap();
+    analysis::mean(&excited)
+}
+
+#[test]
+fn every_rule_samples_the_boltzmann_ratio() {
+    for (beta, gap) in [(1.0, 1.0), (0.5, 3.0), (2.0, 0.2f64)] {
+        let boltzmann = (-beta * gap).exp();
+        let expected = boltzmann / (1.0 + boltzmann);
+        let metropolis = excited_fraction(MetropolisRule, beta, gap);
+        let glauber = excited_fraction(Glauber, beta, gap);
+        assert!(
+            (metropolis - expected).abs() < 0.01,
+            "{metropolis} vs {expected}"
+        );
+        assert!((glauber - expected).abs() < 0.01, "{glauber} vs {expected}");
+    }
+}
+
+#[test]
+fn greedy_never_raises_the_energy() {
+    let ring = Ring::random(100, &mut seed::seeded(3));
+    let params = RingParams { j: 1.0, h: 0.3 };
+    let start = ring.hamiltonian(&params);
+    let (metropolis, (energies,)) = MetropolisBuilder::with_params(params)
+        .state(ring)
+        .beta(0.1)
+        .steps(5_000)
+        .seed(4)
+        .rule(Greedy)
+        .observers((Energy,))
+        .run()
+        .unwrap();
+    assert!(energies.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!(metropolis.energy() < start);
+}
+
+#[test]
+fn infinite_and_nan_energies_are_rejected() {
+    fn check(rule: impl AcceptanceRule) {
+        for beta in [0.0, 1.0] {
+            assert_eq!(rule.probability(f64::INFINITY, beta, 0.0), 0.0);
+            assert_eq!(rule.probability(f64::INFINITY, beta, f64::INFINITY), 0.0);
+            assert_eq!(rule.probability(f64::NAN, beta, 0.0), 0.0);
+        }
+    }
+    check(MetropolisRule);
+    check(Glauber);
+    check(Greedy);
+    assert_eq!(MetropolisRule.probability(-1.0, 1.0, f64::NAN), 0.0);
+    assert_eq!(Glauber.probability(-1.0, 1.0, f64::NAN), 0.0);
+    // leaving an infinite energy is always accepted
+    assert_eq!(MetropolisRule.probability(f64::NEG_INFINITY, 0.0, 0.0), 1.0);
+    assert_eq!(Glauber.probability(f64::NEG_INFINITY, 0.0, 0.0), 1.0);
+}
@@ -0,0 +1,74 @@
+//!
+//! The error analysis against known results: the autocorrelation time of an AR(1)
+//! process, the jackknife of the mean, and series too short to say anything
+//!
+use csta::prelude::*;
+use rand::Rng;
+
+/// x' = φ x + noise, whose autocorrelation at lag t is φ^t
+fn ar1(phi: f64, len: usize, seed: u64) -> Vec<f64> {
+    let mut rng = seed::seeded(seed);
+    let mut x = 0.0;
+    (0..len)
+        .map(|_| {
+            x = phi * x + rng.random_range(-1.0..1.0);
+            x
+        })
+        .collect()
+}
+
+#[test]
+fn ar1_autocorrelation_time_is_exact() {
+    for phi in [0.0, 0.5, 0.8] {
+        let data = ar1(phi, 200_000, 1);
+        let rho = analysis::autocorrelation(&data, 1).unwrap();
+        assert!((rho - phi).abs() < 0.01, "{rho} vs {phi}");
+        // 1/2 + sum of φ^t
+        let exact = (1.0 + phi) / (2.0 * (1.0 - phi));
+        let tau = analysis::integrated_autocorrelation_time(&data);
+        assert!((tau - exact).abs() < 0.05 * exact, "{tau} vs {exact}");
+    }
+}
+
+#[test]
+fn jackknife_of_the_mean_is_the_standard_error() {
+    let data = ar1(0.3, 1_000, 2);
+    let (estimate, error) = analysis::jackknife(&data, 1, analysis::mean).unwrap();
+    let standard_error = (analysis::variance(&data) / data.len() as f64).sqrt();
+    assert!((estimate - analysis::mean(&data)).abs() < 1e-12);
+    assert!((error - standard_error).abs() < 1e-12 * standard_error);
+
+    // and over blocks, the blocking error
+    let (_, error) = analysis::jackknife(&data, 10, analysis::mean).unwrap();
+    let blocking = analysis::blocking_error(&data, 10).unwrap();
+    assert!((error - blocking).abs() < 1e-12 * blocking);
+}
+
+#[test]
+fn too_few_observations_give_nothing() {
+    let data = [1.0, 2.0, 3.0];
+    assert!(analysis::blocks(&data, 0).is_empty());
+    assert_eq!(analysis::blocking_error(&data, 0), None);
+    assert_eq!(analysis::blocking_error(&data, 2), None);
+    assert_eq!(analysis::jackknife(&data, 0, analysis::mean), None);
+    assert_eq!(analysis::jackknife(&data, 2, analysis::mean), None);
+    let mut rng = seed::seeded(3);
+    assert_eq!(
+        analysis::bootstrap(&data, 0, 100, &mut rng, analysis::mean),
+        None
+    );
+    assert_eq!(
+        analysis::bootstrap(&data, 4, 100, &mut rng, analysis::mean),
+        None
+    );
+    assert!(analysis::bootstrap(&data, 1, 100, &mut rng, analysis::mean).is_some());
+}
+
+#[test]
+fn constant_series_have_no_autocorrelation() {
+    let constant = [2.5; 100];
+    assert_eq!(analysis::autocorrelation(&constant, 1), None);
+    assert_eq!(analysis::autocorrelation(&[], 0), None);
+    assert_eq!(analysis::integrated_autocorrelation_time(&constant), 0.5);
+    assert_eq!(analysis::error_of_mean(&constant), 0.0);
+}
@@ -0,0 +1,104 @@
+//!
+//! Schedules give the betas they promise, and annealing keeps the lowest energy it saw
+//!
+mod common;
+
+use common::{Ring, RingParams};
+use csta::prelude::*;
+
+fn assert_close(value: f64, expected: f64) {
+    assert!((value - expected).abs() < 1e-12, "{value} vs {expected}");
+}
+
+#[test]
+fn linear_goes_from_one_beta_to_the_other_and_stays() {
+    let linear = Linear {
+        from: 0.2,
+        to: 1.2,
+        steps: 10,
+    };
+    assert_close(linear.beta(0), 0.2);
+    assert_close(linear.beta(5), 0.7);
+    assert_close(linear.beta(10), 1.2);
+    assert_close(linear.beta(1_000), 1.2);
+}
+
+#[test]
+fn geometric_multiplies_by_the_ratio_every_step() {
+    let geometric = Geometric {
+        from: 0.5,
+        ratio: 1.1,
+    };
+    assert_close(geometric.beta(0), 0.5);
+    assert_close(geometric.beta(3), 0.5 * 1.1 * 1.1 * 1.1);
+}
+
+#[test]
+fn logarithmic_starts_at_infinite_temperature() {
+    let logarithmic = Logarithmic { c: 2.0 };
+    assert_close(logarithmic.beta(0), 0.0);
+    assert_close(logarithmic.beta(9), 10f64.ln() / 2.0);
+}
+
+#[test]
+fn piecewise_runs_each_piece_from_its_start() {
+    let linear = Linear {
+        from: 0.1,
+        to: 1.0,
+        steps: 10,
+    };
+    let geometric = Geometric {
+        from: 2.0,
+        ratio: 2.0,
+    };
+    let piecewise = Piecewise::new(10, linear)
+        .then(5, |_| 1.5)
+        .then(3, geometric);
+    assert_close(piecewise.beta(0), 0.1);
+    assert_close(piecewise.beta(9), linear.beta(9));
+    assert_close(piecewise.beta(10), 1.5);
+    assert_close(piecewise.beta(14), 1.5);
+    assert_close(piecewise.beta(15), 2.0);
+    // the last piece goes on after its steps
+    assert_close(piecewise.beta(20), geometric.beta(5));
+
+    let single = Piecewise::new(0, Logarithmic { c: 1.0 });
+    assert_close(single.beta(0), 0.0);
+    assert_close(single.beta(99), 100f64.ln());
+}
+
+#[test]
+fn annealing_returns_the_lowest_energy_seen_with_its_state() {
+    let ring = Ring::random(64, &mut seed::seeded(1));
+    let params = RingParams { j: 1.0, h: 0.25 };
+    let schedule = Linear {
+        from: 0.1,
+        to: 2.0,
+        steps: 3_000,
+    };
+    let annealing = || {
+        Metropolis::builder()
+            .state(ring.clone())
+            .params(params)
+            .steps(4_000)
+            .seed(2)
+            .build()
+            .unwrap()
+    };
+    let mut metropolis = annealing();
+    let (best, best_energy) = metropolis.run_annealing(&schedule);
+    assert_eq!(best.hamiltonian(&params), best_energy);
+    assert_eq!(metropolis.beta, 2.0);
+
+    // the same run step by step
+    let mut replay = annealing();
+    let mut lowest = replay.energy();
+    for i in 0..replay.steps {
+        replay.beta = schedule.beta(i);
+        replay.step();
+        lowest = lowest.min(replay.energy());
+    }
+    assert_eq!(replay.state, metropolis.state);
+    assert_eq!(best_energy, lowest);
+    assert!(best_energy <= metropolis.energy());
+}
@@ -0,0 +1,98 @@
+//!
+//! The builder refuses every setting that makes no sense, and runs with its observers
+//!
+mod common;
+
+use common::{Energy, Magnetization, Ring};
+use csta::prelude::*;
+
+fn ring() -> Ring {
+    Ring::random(32, &mut seed::seeded(1))
+}
+
+#[test]
+fn a_state_is_needed() {
+    let built = Metropolis::<Ring, _>::builder().steps(10).build();
+    assert_eq!(built.err(), Some(BuildError::MissingState));
+}
+
+#[test]
+fn steps_are_needed() {
+    let built = Metropolis::builder().state(ring()).build();
+    assert_eq!(built.err(), Some(BuildError::MissingSteps));
+}
+
+#[test]
+fn steps_cant_be_zero() {
+    let built = Metropolis::builder().state(ring()).steps(0).build();
+    assert_eq!(built.err(), Some(BuildError::ZeroSteps));
+}
+
+#[test]
+fn beta_has_to_be_finite() {
+    let built = Metropolis::builder()
+        .state(ring())
+        .beta(f64::INFINITY)
+        .steps(10)
+        .build();
+    assert_eq!(built.err(), Some(BuildError::NonFiniteBeta(f64::INFINITY)));
+    let built = Metropolis::builder()
+        .state(ring())
+        .beta(f64::NAN)
+        .steps(10)
+        .build();
+    assert!(matches!(built.err(), Some(BuildError::NonFiniteBeta(beta)) if beta.is_nan()));
+}
+
+#[test]
+fn thermalization_steps_are_not_counted() {
+    let builder = || {
+        Metropolis::builder()
+            .state(ring())
+            .beta(0.3)
+            .steps(10)
+            .seed(2)
+    };
+    let thermalized = builder().thermalization(500).build().unwrap();
+    assert_eq!(thermalized.accepted_moves, 0);
+
+    let mut stepped = builder().build().unwrap();
+    for _ in 0..500 {
+        stepped.step();
+    }
+    assert!(stepped.accepted_moves > 0);
+    assert_eq!(thermalized.state, stepped.state);
+}
+
+#[test]
+fn observers_given_to_the_builder_are_run() {
+    let builder = || {
+        Metropolis::builder()
+            .state(ring())
+            .beta(0.3)
+            .steps(5_000)
+            .thermalization(1_000)
+            .seed(2)
+    };
+    let (metropolis, (energies, magnetizations)) =
+        builder().observers((Energy, Magnetization)).run().unwrap();
+
+    let mut built = builder().build().unwrap();
+    assert_eq!(built.accepted_moves, 0);
+    let (expected_energies, expected_magnetizations) =
+        built.run_with_observers(&(Energy, Magnetization));
+    assert_eq!(energies, expected_energies);
+    assert_eq!(magnetizations, expected_magnetizations);
+    assert_eq!(metropolis.state, built.state);
+    assert_eq!(metropolis.accepted_moves, built.accepted_moves);
+}
+
+#[test]
+fn observers_dont_skip_the_checks() {
+    let run = Metropolis::builder()
+        .state(ring())
+        .steps(0)
+        .observers((Energy,))
+        .run();
+    assert_eq!(run.err(), Some(BuildError::ZeroSteps));
+}
@@ -0,0 +1,54 @@
+//!
+//! The exact steps each cadence measures
+//!
+use csta::prelude::*;
+
+/// Steps of 0..=last the cadence measures
+fn measured(cadence: &Cadence, last: usize) -> Vec<usize> {
+    (0..=last).filter(|&step| cadence.measures(step)).collect()
+}
+
+#[test]
+fn every_skips_the_steps_up_to_after() {
+    assert_eq!(measured(&Cadence::every(3, 4), 15), vec![6, 9, 12, 15]);
+    assert_eq!(measured(&Cadence::every(1, 0), 3), vec![1, 2, 3]);
+}
+
+#[test]
+fn logarithmic_measures_per_decade_steps_for_each_power_of_ten() {
+    // 10^(k/2) rounded: 1, 3.16, 10, 31.6, 100
+    assert_eq!(
+        measured(&Cadence::logarithmic(2, 0), 100),
+        vec![1, 3, 10, 32, 100]
+    );
+}
+
+#[test]
+fn logarithmic_counts_from_after() {
+    assert_eq!(
+        measured(&Cadence::logarithmic(2, 5), 105),
+        vec![6, 8, 15, 37, 105]
+    );
+}
+
+#[test]
+fn logarithmic_without_steps_per_decade_measures_nothing() {
+    assert!(measured(&Cadence::logarithmic(0, 0), 1_000).is_empty());
+    assert!(measured(&Cadence::logarithmic(0, 10), 1_000).is_empty());
+}
+
+#[test]
+fn logarithmic_measures_steps_that_round_the_same_once() {
+    // 10^(k/10) rounded: 1, 1, 2, 2, 3, 3, 4, 5, 6, 8, 10
+    assert_eq!(
+        measured(&Cadence::logarithmic(10, 0), 10),
+        vec![1, 2, 3, 4, 5, 6, 8, 10]
+    );
+}
+
+#[test]
+fn explicit_steps_are_sorted_and_deduplicated() {
+    let cadence = Cadence::steps(vec![50, 3, 20, 3, 0]);
+    assert_eq!(cadence, Cadence::Steps(vec![0, 3, 20, 50]));
+    assert_eq!(measured(&cadence, 100), vec![0, 3, 20, 50]);
+}
@@ -0,0 +1,98 @@
+//!
+//! R-hat and the effective sample size of several chains: chains that agree,
+//! chains that don't, and each observer of a set run on independent chains
+//!
+mod common;
+
+use common::{Energy, Magnetization, Ring};
+use csta::prelude::*;
+use rand::Rng;
+
+fn uniform(len: usize, shift: f64, rng: &mut impl Rng) -> Vec<f64> {
+    (0..len).map(|_| shift + rng.random::<f64>()).collect()
+}
+
+#[test]
+fn agreeing_chains_have_an_r_hat_of_one() {
+    let mut rng = seed::seeded(1);
+    let chains: Vec<Vec<f64>> = (0..4).map(|_| uniform(2_000, 0.0, &mut rng)).collect();
+    let convergence = analysis::convergence(&chains);
+    assert!((convergence.r_hat - 1.0).abs() < 0.01, "{convergence:?}");
+    // independent observations, each one counts
+    assert!(
+        (convergence.ess / 8_000.0 - 1.0).abs() < 0.1,
+        "{convergence:?}"
+    );
+}
+
+#[test]
+fn shifted_chains_have_a_large_r_hat() {
+    let mut rng = seed::seeded(2);
+    let chains: Vec<Vec<f64>> = (0..4).map(|i| uniform(2_000, i as f64, &mut rng)).collect();
+    let r_hat = analysis::split_r_hat(&chains);
+    assert!(r_hat > 2.0, "{r_hat}");
+}
+
+#[test]
+fn a_chain_drifting_within_itself_is_not_converged() {
+    // the split halves of a trend disagree, even with a single chain
+    let trend: Vec<f64> = (0..1_000).map(|i| i as f64 / 100.0).collect();
+    assert!(analysis::split_r_hat(&[trend]) > 2.0);
+    assert!(analysis::split_r_hat(&[[1.0, 2.0, 3.0]]).is_nan());
+}
+
+#[test]
+fn each_observer_of_a_set_gets_its_convergence() {
+    let mut chains = Chains::from_samples(
+        seed::streams(3).map(|mut rng| (Ring::random(64, &mut rng), rng)),
+        4,
+        |(ring, rng)| {
+            Metropolis::builder()
+                .state(ring)
+                .beta(0.2)
+                .steps(20_000)
+                .thermalization(5_000)
+                .rng(rng)
+                .build()
+                .unwrap()
+        },
+    );
+    let runs = chains.run_with_observers(&(Energy, Magnetization));
+    assert_eq!(runs.len(), 4);
+    let energy = analysis::convergence_of(&runs, |(energies, _)| energies);
+    let magnetization = analysis::convergence_of(&runs, |(_, magnetizations)| magnetizations);
+    assert!((energy.r_hat - 1.0).abs() < 0.05, "{energy:?}");
+    assert!(
+        (magnetization.r_hat - 1.0).abs() < 0.05,
+        "{magnetization:?}"
+    );
+
+    let energies: Vec<&Vec<f64>> = runs.iter().map(|(energies, _)| energies).collect();
+    assert_eq!(energy, analysis::convergence(&energies));
+}
+
+#[test]
+fn glauber_chains_sample_the_ring() {
+    let mut chains = Chains::from_samples(
+        seed::streams(4).map(|mut rng| (Ring::random(64, &mut rng), rng)),
+        4,
+        |(ring, rng)| {
+            Metropolis::builder()
+                .state(ring)
+                .beta(0.5)
+                .steps(40_000)
+                .thermalization(5_000)
+                .rng(rng)
+                .rule(Glauber)
+                .build()
+                .unwrap()
+        },
+    );
+    let energies = chains.run_with::<Energy>();
+    let convergence = analysis::convergence(&energies);
+    assert!((convergence.r_hat - 1.0).abs() < 0.05, "{convergence:?}");
+    // the energy per site of an infinite ring is -j tanh(beta j)
+    let per_site = energies.iter().map(|e| analysis::mean(e)).sum::<f64>() / (4.0 * 64.0);
+    let exact = -(0.5f64).tanh();
+    assert!((per_site - exact).abs() < 0.02, "{per_site} vs {exact}");
+}
@@ -0,0 +1,147 @@
+//!
+//! A run resumed from a checkpoint has to give the same observations as the uninterrupted run
+//!
+use csta::{csta_derive::Randomizable, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Randomizable, Serialize, Deserialize)]
+struct Walker {
+    #[csta(range(-1.0..1.0))]
+    x: f64,
+}
+
+impl State for Walker {
+    type Params = f64;
+    type Change = f64;
+
+    fn energy(&self, k: &mut Self::Params) -> f64 {
+        0.5 * *k * self.x * self.x
+    }
+
+    fn propose_change(&self, rng: &mut impl rand::Rng) -> Self::Change {
+        rng.random_range(-0.5..0.5)
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.x += change;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.x -= change;
+    }
+}
+
+struct Position;
+
+impl Observer<Walker> for Position {
+    type Observation = f64;
+
+    fn measure(&self, state: &Walker, _params: &f64) -> Self::Observation {
+        state.x
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(3, 10)
+    }
+}
+
+fn metropolis() -> Metropolis<Walker, SeedRng> {
+    MetropolisBuilder::with_params(2.0)
+        .state(Walker { x: 0.3 })
+        .beta(1.5)
+        .steps(3_000)
+        .seed(17)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let mut uninterrupted = metropolis();
+    let expected = uninterrupted.run_with_observer(&Position);
+
+    // keep the checkpoint at step 1_000 and "crash" right after it
+    let mut saved = None;
+    let crashed = metropolis().run_with_checkpoints(&Position, 500, |checkpoint| {
+        if checkpoint.step == 1_000 {
+            saved = Some(serde_json::to_string(checkpoint).unwrap());
+            return Err("crash");
+        }
+        Ok(())
+    });
+    assert_eq!(crashed, Err("crash"));
+
+    let checkpoint: LoadedCheckpoint<Walker, SeedRng, f64> =
+        serde_json::from_str(&saved.unwrap()).unwrap();
+    let (resumed, observations) =
+        Metropolis::resume_with(checkpoint, MetropolisRule, None, &Position, 500, |_| {
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+
+    assert_eq!(observations, expected);
+    assert_eq!(resumed.state, uninterrupted.state);
+    assert_eq!(resumed.accepted_moves, uninterrupted.accepted_moves);
+    assert_eq!(resumed.energy(), uninterrupted.energy());
+}
+
+fn moves() -> MoveSet<Walker> {
+    MoveSet::new()
+        .with("small", 3.0, |_, rng| rng.random_range(-0.1..0.1))
+        .with("large", 1.0, |_, rng| rng.random_range(-2.0..2.0))
+}
+
+#[test]
+fn resumed_run_keeps_its_rule_and_moves() {
+    let glauber = || {
+        MetropolisBuilder::with_params(2.0)
+            .state(Walker { x: 0.3 })
+            .beta(1.5)
+            .steps(3_000)
+            .seed(18)
+            .rule(Glauber)
+            .moves(moves())
+            .build()
+            .unwrap()
+    };
+    let mut uninterrupted = glauber();
+    let expected = uninterrupted.run_with_observer(&Position);
+
+    let mut crashed = glauber();
+    let mut saved = None;
+    let result = crashed.run_with_checkpoints(&Position, 700, |checkpoint| {
+        if checkpoint.step == 1_400 {
+            saved = Some(serde_json::to_string(checkpoint).unwrap());
+            return Err("crash");
+        }
+        Ok(())
+    });
+    assert_eq!(result, Err("crash"));
+
+    let checkpoint: LoadedCheckpoint<Walker, SeedRng, f64> =
+        serde_json::from_str(&saved.unwrap()).unwrap();
+    // the moves of the crashed run, with their counts up to the checkpoint
+    let moves = crashed.move_set_mut().map(std::mem::take);
+    let (resumed, observations) = Metropolis::resume_with(
+        checkpoint,
+        Glauber,
+        moves,
+        &Position,
+        0,
+        |_| Ok::<_, ()>(()),
+    )
+    .unwrap();
+
+    assert_eq!(observations, expected);
+    assert_eq!(resumed.state, uninterrupted.state);
+    assert_eq!(resumed.accepted_moves, uninterrupted.accepted_moves);
+    let (resumed, uninterrupted) = (
+        resumed.move_set().unwrap(),
+        uninterrupted.move_set().unwrap(),
+    );
+    for name in ["small", "large"] {
+        let (a, b) = (resumed.get(name).unwrap(), uninterrupted.get(name).unwrap());
+        assert_eq!((a.attempted, a.accepted), (b.attempted, b.accepted));
+    }
+}
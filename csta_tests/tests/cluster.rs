@@ -0,0 +1,75 @@
+//!
+//! Cluster updates have to sample the same equilibrium as single spin flips
+//!
+use csta::{
+    models::{ising::*, observables::AbsMagnetization},
+    prelude::*,
+};
+
+const L: usize = 8;
+
+/// Periodic L x L ising with every spin up
+fn ordered() -> Ising {
+    Ising::ordered(Lattice::square(L, L, Boundary::Periodic))
+}
+
+fn single_flip(beta: f64) -> f64 {
+    let mut metropolis = MetropolisBuilder::with_params(IsingParams::default())
+        .state(ordered())
+        .beta(beta)
+        .steps(L * L)
+        .seed(3)
+        .build()
+        .unwrap();
+    // measure once per sweep
+    let mut measures = Vec::new();
+    for _ in 0..4_000 {
+        metropolis.run_empty();
+        measures.push(AbsMagnetization.measure(&metropolis.state, &metropolis.params));
+    }
+    analysis::mean(&measures[200..])
+}
+
+fn cluster(update: ClusterUpdate, beta: f64) -> f64 {
+    let steps = match update {
+        ClusterUpdate::Wolff => 20_000,
+        ClusterUpdate::SwendsenWang => 4_000,
+    };
+    let rng = seed::seeded(5);
+    let params = IsingParams::default();
+    let mut cluster = Cluster::new(ordered(), params, beta, steps, update, rng).unwrap();
+    analysis::mean(&cluster.run_with_observer(&Scheduled {
+        observer: AbsMagnetization,
+        cadence: Cadence::every(1, 100),
+    }))
+}
+
+#[test]
+fn cluster_updates_match_single_flip_magnetization() {
+    // disordered, critical and ordered
+    for beta in [0.3, 0.44, 0.6] {
+        let expected = single_flip(beta);
+        for update in [ClusterUpdate::Wolff, ClusterUpdate::SwendsenWang] {
+            let magnetization = cluster(update, beta);
+            assert!(
+                (magnetization - expected).abs() < 0.03,
+                "{update:?} at beta {beta}: {magnetization} vs {expected}"
+            );
+        }
+    }
+}
+
+#[test]
+fn wolff_flips_the_whole_lattice_at_low_temperature() {
+    let params = IsingParams::default();
+    let mut cluster = Cluster::wolff(ordered(), params, 5.0, 1, seed::seeded(1)).unwrap();
+    assert_eq!(cluster.step(), L * L);
+    assert!(cluster.state.spins.iter().all(|&s| s == -1));
+}
+
+#[test]
+fn cluster_updates_need_a_finite_beta() {
+    let params = IsingParams::default();
+    let error = Cluster::swendsen_wang(ordered(), params, f64::NAN, 1, seed::seeded(1)).err();
+    assert!(matches!(error, Some(ClusterError::NonFiniteBeta(_))));
+}
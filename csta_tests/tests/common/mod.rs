@@ -0,0 +1,121 @@
+//!
+//! Fixtures shared by the tests: an ising ring that knows its delta energy,
+//! and observers of its energy and magnetization
+//!
+#![allow(dead_code)]
+
+use csta::prelude::*;
+use rand::Rng;
+
+/// Periodic ising chain, E = -j sum s_i s_i+1 - h sum s_i
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ring {
+    pub spins: Vec<i8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RingParams {
+    pub j: f64,
+    pub h: f64,
+}
+
+impl Default for RingParams {
+    fn default() -> Self {
+        Self { j: 1.0, h: 0.0 }
+    }
+}
+
+impl Ring {
+    pub fn random(len: usize, rng: &mut impl Rng) -> Self {
+        Self {
+            spins: (0..len)
+                .map(|_| if rng.random() { 1 } else { -1 })
+                .collect(),
+        }
+    }
+
+    /// Same as [`State::energy`], without needing the params mutably
+    pub fn hamiltonian(&self, params: &RingParams) -> f64 {
+        let n = self.spins.len();
+        (0..n)
+            .map(|i| {
+                let s = f64::from(self.spins[i]);
+                -params.j * s * f64::from(self.spins[(i + 1) % n]) - params.h * s
+            })
+            .sum()
+    }
+
+    fn local_field(&self, site: usize, params: &RingParams) -> f64 {
+        let n = self.spins.len();
+        let neighbours = f64::from(self.spins[(site + 1) % n] + self.spins[(site + n - 1) % n]);
+        params.j * neighbours + params.h
+    }
+}
+
+impl State for Ring {
+    type Params = RingParams;
+    /// the site flipped
+    type Change = usize;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.hamiltonian(params)
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        rng.random_range(0..self.spins.len())
+    }
+
+    fn apply_change(&mut self, site: Self::Change) {
+        self.spins[site] = -self.spins[site];
+    }
+
+    fn revert_change(&mut self, site: Self::Change) {
+        self.spins[site] = -self.spins[site];
+    }
+
+    fn delta_energy(&self, site: &Self::Change, params: &mut Self::Params) -> Option<f64> {
+        Some(2.0 * f64::from(self.spins[*site]) * self.local_field(*site, params))
+    }
+}
+
+/// Energy of the ring, from the step after the first on
+#[derive(Default)]
+pub struct Energy;
+
+impl Observer<Ring> for Energy {
+    type Observation = f64;
+
+    fn measure(&self, state: &Ring, params: &RingParams) -> Self::Observation {
+        state.hamiltonian(params)
+    }
+
+    /// The energy kept by the run, instead of summing the hamiltonian again
+    fn measure_with_energy(
+        &self,
+        _state: &Ring,
+        _params: &RingParams,
+        energy: f64,
+    ) -> Self::Observation {
+        energy
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 0)
+    }
+}
+
+/// Sum of the spins of the ring, every step
+#[derive(Default, Clone)]
+pub struct Magnetization;
+
+impl Observer<Ring> for Magnetization {
+    type Observation = f64;
+
+    fn measure(&self, state: &Ring, _params: &RingParams) -> Self::Observation {
+        state.spins.iter().map(|&s| f64::from(s)).sum()
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 0)
+    }
+}
@@ -0,0 +1,124 @@
+//!
+//! The energy metropolis keeps: the same with or without delta energies,
+//! and the one the energy observers are given
+//!
+mod common;
+
+use common::{Energy, Ring, RingParams};
+use csta::prelude::*;
+
+/// Ring that doesn't know its delta energy, so metropolis applies, computes and reverts
+#[derive(Debug, Clone, PartialEq)]
+struct Recomputed(Ring);
+
+impl State for Recomputed {
+    type Params = RingParams;
+    type Change = usize;
+
+    fn energy(&self, params: &mut Self::Params) -> f64 {
+        self.0.energy(params)
+    }
+
+    fn propose_change(&self, rng: &mut impl rand::Rng) -> Self::Change {
+        self.0.propose_change(rng)
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.0.apply_change(change);
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.0.revert_change(change);
+    }
+}
+
+impl Observer<Recomputed> for Energy {
+    type Observation = f64;
+
+    fn measure(&self, state: &Recomputed, params: &RingParams) -> Self::Observation {
+        state.0.hamiltonian(params)
+    }
+
+    fn measure_with_energy(
+        &self,
+        _state: &Recomputed,
+        _params: &RingParams,
+        energy: f64,
+    ) -> Self::Observation {
+        energy
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 0)
+    }
+}
+
+fn seeded<S: State>(
+    state: S,
+    params: S::Params,
+    beta: f64,
+    steps: usize,
+    seed: u64,
+) -> Metropolis<S, SeedRng> {
+    MetropolisBuilder::with_params(params)
+        .state(state)
+        .beta(beta)
+        .steps(steps)
+        .seed(seed)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn delta_energies_and_recomputed_energies_make_the_same_chain() {
+    let ring = Ring::random(64, &mut seed::seeded(1));
+    // with a dyadic field every energy is exact, whatever the order of the sums
+    let params = RingParams { j: 1.0, h: 0.5 };
+
+    let mut delta = seeded(ring.clone(), params, 0.4, 20_000, 2);
+    let mut recomputed = seeded(Recomputed(ring), params, 0.4, 20_000, 2);
+    let delta_energies = delta.run_with::<Energy>();
+    let recomputed_energies = recomputed.run_with::<Energy>();
+
+    assert_eq!(delta.state, recomputed.state.0);
+    assert_eq!(delta.accepted_moves, recomputed.accepted_moves);
+    assert_eq!(delta.energy(), recomputed.energy());
+    assert_eq!(delta.energy(), delta.state.hamiltonian(&params));
+    assert_eq!(delta_energies, recomputed_energies);
+}
+
+#[test]
+fn energy_observers_are_given_the_kept_energy() {
+    let ring = Ring::random(36, &mut seed::seeded(3));
+    let params = RingParams { j: 1.0, h: 0.25 };
+    let mut metropolis = seeded(ring.clone(), params, 0.3, 5_000, 4);
+    let energies = metropolis.run_with::<Energy>();
+
+    // the observations match the hamiltonian of the measured states
+    let mut replay = seeded(ring, params, 0.3, 5_000, 4);
+    // measured from the first step on
+    for (i, energy) in energies.iter().enumerate() {
+        replay.step();
+        assert_eq!(
+            *energy,
+            replay.state.hamiltonian(&params),
+            "at step {}",
+            i + 1
+        );
+    }
+    // the kept energy, not the one of a state modified by hand
+    metropolis.state.spins.fill(1);
+    assert_eq!(
+        Observer::<Ring>::measure_with_energy(
+            &Energy,
+            &metropolis.state,
+            &params,
+            metropolis.energy()
+        ),
+        metropolis.energy()
+    );
+    assert_eq!(
+        metropolis.refresh_energy(),
+        Observer::<Ring>::measure(&Energy, &metropolis.state, &params)
+    );
+}
@@ -0,0 +1,258 @@
+//!
+//! Grand canonical and isobaric ideal gases against their exact averages,
+//! the energies metropolis keeps of interacting particles,
+//! and volume changes that would overlap particles or outgrow the cutoff
+//!
+use csta::{models::particles::*, prelude::*};
+
+type Ideal = Particles<HardSphere>;
+
+/// Particles that don't interact
+fn ideal() -> HardSphere {
+    HardSphere { diameter: 0.0 }
+}
+
+fn assert_close(value: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (value - expected).abs() < tolerance,
+        "{value} vs {expected}"
+    );
+}
+
+#[derive(Default)]
+struct Count;
+
+impl Observer<GrandCanonical<Ideal>> for Count {
+    type Observation = f64;
+
+    fn measure(
+        &self,
+        state: &GrandCanonical<Ideal>,
+        _params: &GrandCanonicalParams<HardSphere>,
+    ) -> Self::Observation {
+        state.system.len() as f64
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(10, 10_000)
+    }
+}
+
+#[derive(Default)]
+struct Volume;
+
+impl Observer<Isobaric<Ideal>> for Volume {
+    type Observation = f64;
+
+    fn measure(
+        &self,
+        state: &Isobaric<Ideal>,
+        _params: &IsobaricParams<HardSphere>,
+    ) -> Self::Observation {
+        state.system.volume()
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(10, 10_000)
+    }
+}
+
+#[test]
+fn ideal_gas_number_is_the_activity_times_the_volume() {
+    let (beta, activity) = (1.0, 0.2f64);
+    let empty = GrandCanonical::new(Ideal::new(Vec::new(), Vec3f64(5.0, 5.0, 5.0)));
+    let params = GrandCanonicalParams::new(ideal(), activity.ln() / beta);
+    let mut metropolis = MetropolisBuilder::with_params(params)
+        .state(empty)
+        .beta(beta)
+        .steps(200_000)
+        .seed(1)
+        .build()
+        .unwrap();
+    let counts = metropolis.run_with::<Count>();
+    assert_close(analysis::mean(&counts), activity * 125.0, 1.0);
+}
+
+#[test]
+fn ideal_gas_volume_is_the_number_over_the_pressure() {
+    let (beta, pressure, n) = (2.0, 1.0, 20);
+    let gas = Isobaric::new(Ideal::grid(n, 3.0))
+        .with_volume_weight(1.0)
+        .with_volume_scale(0.3);
+    let params = IsobaricParams {
+        system: ideal(),
+        pressure,
+    };
+    let mut metropolis = MetropolisBuilder::with_params(params)
+        .state(gas)
+        .beta(beta)
+        .steps(200_000)
+        .seed(2)
+        .build()
+        .unwrap();
+    let volumes = metropolis.run_with::<Volume>();
+    // V^N exp(-βPV) is a gamma distribution, of mean (N + 1) / βP
+    assert_close(
+        analysis::mean(&volumes),
+        (n + 1) as f64 / (beta * pressure),
+        0.2,
+    );
+}
+
+#[test]
+fn hard_spheres_are_never_inserted_overlapping() {
+    let side = 6.0;
+    let empty = GrandCanonical::new(Particles::new(Vec::new(), Vec3f64(side, side, side)))
+        .with_exchange_weight(0.3);
+    let mut metropolis =
+        MetropolisBuilder::with_params(GrandCanonicalParams::new(HardSphere::default(), 2.0))
+            .state(empty)
+            .steps(50_000)
+            .seed(3)
+            .build()
+            .unwrap();
+    metropolis.run_empty();
+    let system = &metropolis.state.system;
+    assert!(system.len() > 20);
+    assert!(!system.overlaps(&metropolis.params.system));
+}
+
+#[test]
+fn overlapping_insertions_and_volumes_are_invalid() {
+    let hard = HardSphere::default();
+    let dense = || Particles::grid(27, 4.5);
+    let (open, params) = (
+        GrandCanonical::new(dense()),
+        GrandCanonicalParams::new(hard, 5.0),
+    );
+    let insertion = GrandCanonicalChange::Insert(Vec3f64(0.1, 0.1, 0.1));
+    assert!(open.check_change(&insertion, &params).is_err());
+    let far = GrandCanonicalChange::Insert(Vec3f64(0.75, 0.75, 0.75));
+    assert!(open.check_change(&far, &params).is_ok());
+
+    let closed = Isobaric::new(dense());
+    let params = IsobaricParams {
+        system: hard,
+        pressure: 1.0,
+    };
+    let shrink = |to| IsobaricChange::Volume {
+        from: closed.system.volume(),
+        to,
+    };
+    // spheres 1.5 apart touch once the sides shrink by 1 / 1.5
+    let volume = closed.system.volume();
+    assert!(
+        closed
+            .check_change(&shrink(volume * 0.7f64.powi(3)), &params)
+            .is_ok()
+    );
+    assert!(
+        closed
+            .check_change(&shrink(volume * 0.65f64.powi(3)), &params)
+            .is_err()
+    );
+    assert!(closed.check_change(&shrink(2.0 * volume), &params).is_ok());
+}
+
+#[test]
+fn boxes_stay_twice_as_wide_as_the_cutoff() {
+    let params = |cutoff| IsobaricParams {
+        system: LennardJones {
+            cutoff,
+            ..LennardJones::default()
+        },
+        pressure: 1.0,
+    };
+    let shrink = |closed: &Isobaric<Particles<LennardJones>>, stretch: f64| {
+        let from = closed.system.volume();
+        IsobaricChange::Volume {
+            from,
+            to: from * stretch.powi(3),
+        }
+    };
+    // sides of 6, the cutoff can't go past 3
+    let closed = Isobaric::new(Particles::grid(8, 6.0));
+    assert!(
+        closed
+            .check_change(&shrink(&closed, 0.9), &params(2.5))
+            .is_ok()
+    );
+    assert!(
+        closed
+            .check_change(&shrink(&closed, 0.8), &params(2.5))
+            .is_err()
+    );
+    assert!(
+        closed
+            .check_change(&shrink(&closed, 0.8), &params(f64::INFINITY))
+            .is_ok()
+    );
+
+    // nor can the cells, wider than the potential needs
+    let cells = Isobaric::new(Particles::grid(8, 6.0).with_cell_list(2.5));
+    assert!(
+        cells
+            .check_change(&shrink(&cells, 0.9), &params(1.0))
+            .is_ok()
+    );
+    assert!(
+        cells
+            .check_change(&shrink(&cells, 0.8), &params(1.0))
+            .is_err()
+    );
+}
+
+#[test]
+fn compressed_hard_spheres_never_overlap() {
+    let gas = Isobaric::new(Particles::grid(27, 4.5)).with_volume_scale(0.05);
+    let params = IsobaricParams {
+        system: HardSphere::default(),
+        pressure: 20.0,
+    };
+    let mut metropolis = MetropolisBuilder::with_params(params)
+        .state(gas)
+        .steps(50_000)
+        .seed(6)
+        .build()
+        .unwrap();
+    metropolis.run_empty();
+    assert!(!metropolis.state.system.overlaps(&metropolis.params.system));
+    assert!(metropolis.energy().is_finite());
+    assert!(metropolis.state.system.volume() < 4.5f64.powi(3));
+}
+
+#[test]
+fn metropolis_keeps_the_grand_potential_and_the_enthalpy() {
+    let particles = || Particles::grid(64, 6.0).with_cell_list(2.5);
+
+    let open = GrandCanonical::new(particles());
+    let params = GrandCanonicalParams::new(LennardJones::default(), -2.0);
+    let mut metropolis = MetropolisBuilder::with_params(params)
+        .state(open)
+        .steps(20_000)
+        .seed(4)
+        .build()
+        .unwrap();
+    metropolis.run_empty();
+    let exact = metropolis.state.energy(&mut metropolis.params);
+    assert_close(metropolis.energy(), exact, 1e-6 * exact.abs());
+    assert_ne!(metropolis.state.system.len(), 64);
+    let cells = metropolis.state.system.cell_list().unwrap();
+    assert_eq!(cells.len(), metropolis.state.system.len());
+
+    let closed = Isobaric::new(particles());
+    let params = IsobaricParams {
+        system: LennardJones::default(),
+        pressure: 1.0,
+    };
+    let mut metropolis = MetropolisBuilder::with_params(params)
+        .state(closed)
+        .steps(20_000)
+        .seed(5)
+        .build()
+        .unwrap();
+    metropolis.run_empty();
+    let exact = metropolis.state.energy(&mut metropolis.params);
+    assert_close(metropolis.energy(), exact, 1e-6 * exact.abs());
+    assert_ne!(metropolis.state.system.volume(), 216.0);
+}
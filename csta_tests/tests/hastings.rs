@@ -0,0 +1,104 @@
+//!
+//! A walk on a ring that steps forward more often than backwards samples
+//! the Boltzmann weights once the Hastings correction accounts for the bias
+//!
+use csta::prelude::*;
+use rand::Rng;
+
+const ENERGIES: [f64; 5] = [0.0, 1.0, 0.5, 2.0, 1.5];
+
+/// Probability of proposing a step forward
+const FORWARD: f64 = 0.8;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Ring {
+    site: usize,
+}
+
+impl State for Ring {
+    /// whether the bias is corrected
+    type Params = bool;
+    /// step forward or backwards
+    type Change = bool;
+
+    fn energy(&self, _corrected: &mut Self::Params) -> f64 {
+        ENERGIES[self.site]
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        rng.random_bool(FORWARD)
+    }
+
+    fn apply_change(&mut self, forward: Self::Change) {
+        self.site = if forward {
+            (self.site + 1) % ENERGIES.len()
+        } else {
+            (self.site + ENERGIES.len() - 1) % ENERGIES.len()
+        };
+    }
+
+    fn revert_change(&mut self, forward: Self::Change) {
+        self.apply_change(!forward);
+    }
+
+    fn proposal_log_ratio(&self, forward: &Self::Change, corrected: &Self::Params) -> f64 {
+        if !corrected {
+            return 0.0;
+        }
+        // the way back is a step the other way
+        let (there, back) = if *forward {
+            (FORWARD, 1.0 - FORWARD)
+        } else {
+            (1.0 - FORWARD, FORWARD)
+        };
+        back.ln() - there.ln()
+    }
+}
+
+#[derive(Default)]
+struct Site;
+
+impl Observer<Ring> for Site {
+    type Observation = usize;
+
+    fn measure(&self, state: &Ring, _corrected: &bool) -> Self::Observation {
+        state.site
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(1, 1_000)
+    }
+}
+
+/// Largest difference between the fraction of time at each site and its Boltzmann weight
+fn histogram_error(corrected: bool) -> f64 {
+    let beta = 1.0;
+    let sites = MetropolisBuilder::with_params(corrected)
+        .state(Ring { site: 0 })
+        .beta(beta)
+        .steps(400_000)
+        .seed(1)
+        .build()
+        .unwrap()
+        .run_with::<Site>();
+    let weights = ENERGIES.map(|energy| (-beta * energy).exp());
+    let z: f64 = weights.iter().sum();
+    (0..ENERGIES.len())
+        .map(|site| {
+            let fraction = sites.iter().filter(|&&s| s == site).count() as f64 / sites.len() as f64;
+            (fraction - weights[site] / z).abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn corrected_biased_walk_samples_the_boltzmann_weights() {
+    let error = histogram_error(true);
+    assert!(error < 0.005, "{error}");
+}
+
+#[test]
+fn uncorrected_biased_walk_does_not() {
+    let error = histogram_error(false);
+    assert!(error > 0.05, "{error}");
+}
@@ -0,0 +1,90 @@
+//!
+//! Changes that can't be made are rejected and counted apart from the energetic rejections
+//!
+use csta::prelude::*;
+use rand::Rng;
+
+/// Free particle in the box [0, 1), moves leaving it are invalid
+#[derive(Debug, Clone, PartialEq)]
+struct Boxed {
+    x: f64,
+    /// whether the box is checked before applying, or while applying
+    check_first: bool,
+}
+
+impl State for Boxed {
+    type Params = ();
+    type Change = f64;
+
+    fn energy(&self, _params: &mut Self::Params) -> f64 {
+        0.0
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        rng.random_range(-0.5..0.5)
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.x += change;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.x -= change;
+    }
+
+    fn check_change(&self, change: &f64, _params: &()) -> Result<(), ModificationError> {
+        if self.check_first && !(0.0..1.0).contains(&(self.x + change)) {
+            return Err(ModificationError::new("out of the box"));
+        }
+        Ok(())
+    }
+
+    fn try_apply_change(&mut self, change: Self::Change) -> Result<(), ModificationError> {
+        if !(0.0..1.0).contains(&(self.x + change)) {
+            return Err(ModificationError::new("out of the box"));
+        }
+        self.apply_change(change);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Position;
+
+impl Observer<Boxed> for Position {
+    type Observation = f64;
+
+    fn measure(&self, state: &Boxed, _params: &()) -> Self::Observation {
+        state.x
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(5, 0)
+    }
+}
+
+#[test]
+fn invalid_changes_are_rejected_and_counted() {
+    for check_first in [true, false] {
+        let state = Boxed {
+            x: 0.5,
+            check_first,
+        };
+        let mut metropolis = MetropolisBuilder::with_params(())
+            .state(state)
+            .steps(50_000)
+            .seed(9)
+            .build()
+            .unwrap();
+        let positions = metropolis.run_with::<Position>();
+
+        assert!(positions.iter().all(|x| (0.0..1.0).contains(x)));
+        assert!((analysis::mean(&positions) - 0.5).abs() < 0.02);
+        // without energy, every valid change is accepted
+        assert!(metropolis.invalid_moves > 0);
+        assert_eq!(metropolis.accepted_moves + metropolis.invalid_moves, 50_000);
+        assert!(metropolis.energetic_rejected_rate().abs() < 1e-12);
+        // half a box of moves, a quarter of them leaves it on average
+        assert!((metropolis.invalid_rate() - 0.25).abs() < 0.02);
+    }
+}
@@ -0,0 +1,70 @@
+//!
+//! Neighbour tables of the lattices, and of graphs built by hand
+//!
+use csta::prelude::*;
+
+fn sorted(neighbours: &[usize]) -> Vec<usize> {
+    let mut neighbours = neighbours.to_vec();
+    neighbours.sort_unstable();
+    neighbours
+}
+
+#[test]
+fn every_lattice_has_its_coordination_number() {
+    let lattices = [
+        (Lattice::chain(7, Boundary::Periodic), 2),
+        (Lattice::square(4, 5, Boundary::Periodic), 4),
+        (Lattice::triangular(4, 5, Boundary::Periodic), 6),
+        (Lattice::honeycomb(4, 5, Boundary::Periodic), 3),
+        (Lattice::cubic(3, 4, 5, Boundary::Periodic), 6),
+    ];
+    for (lattice, coordination) in lattices {
+        for site in 0..lattice.len() {
+            assert_eq!(lattice.neighbours(site).len(), coordination, "{lattice:?}");
+            // bonds are symmetric
+            for &other in lattice.neighbours(site) {
+                assert!(lattice.neighbours(other).contains(&site));
+            }
+        }
+        assert_eq!(
+            lattice.graph().edge_count(),
+            lattice.len() * coordination / 2
+        );
+    }
+}
+
+#[test]
+fn periodic_square_wraps_and_open_square_does_not() {
+    let periodic = Lattice::square(4, 3, Boundary::Periodic);
+    assert_eq!(sorted(periodic.neighbours(0)), vec![1, 3, 4, 8]);
+    // the end of a row doesn't continue on the next one
+    assert_eq!(sorted(periodic.neighbours(3)), vec![0, 2, 7, 11]);
+
+    let open = Lattice::square(4, 3, Boundary::Open);
+    assert_eq!(sorted(open.neighbours(0)), vec![1, 4]);
+    assert_eq!(sorted(open.neighbours(3)), vec![2, 7]);
+    assert_eq!(sorted(open.neighbours(5)), vec![1, 4, 6, 9]);
+    assert_eq!(open.edges().count(), 3 * 3 + 4 * 2);
+}
+
+#[test]
+fn sites_and_cells_are_inverses() {
+    let lattice = Lattice::honeycomb(3, 4, Boundary::Open);
+    assert_eq!(lattice.len(), 24);
+    for site in 0..lattice.len() {
+        let (cell, sublattice) = lattice.cell(site);
+        assert_eq!(lattice.site(&cell, sublattice), site);
+    }
+    assert_eq!(lattice.site(&[1, 2], 1), 2 * (1 + 3 * 2) + 1);
+}
+
+#[test]
+fn graph_keeps_repeated_bonds_and_drops_self_loops() {
+    let graph = Graph::from_edges(3, [(0, 1), (1, 2), (1, 2), (2, 2)]);
+    assert_eq!(graph.neighbours(1), &[0, 2, 2]);
+    assert_eq!(graph.degree(2), 2);
+    assert_eq!(
+        graph.edges().collect::<Vec<_>>(),
+        vec![(0, 1), (1, 2), (1, 2)]
+    );
+}
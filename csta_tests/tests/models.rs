@@ -0,0 +1,244 @@
+//!
+//! The models against exact results: 1D chains, free spins in a field,
+//! the critical temperature of the 2D ising model, and the params
+//! that cluster updates can't sample
+//!
+use csta::{
+    models::{Model, heisenberg::*, ising::*, lattice_gas::*, observables::*, potts::*, xy::*},
+    prelude::*,
+};
+
+const N: usize = 64;
+const SWEEPS: usize = 3_000;
+
+fn chain() -> Lattice {
+    Lattice::chain(N, Boundary::Periodic)
+}
+
+/// Mean of the observer measured once per sweep, after 10% of burn-in
+fn sweep_average<S, O>(state: S, params: S::Params, beta: f64, observer: O) -> f64
+where
+    S: Model,
+    O: Observer<S, Observation = f64>,
+{
+    let mut metropolis = MetropolisBuilder::with_params(params)
+        .state(state)
+        .beta(beta)
+        .steps(N * SWEEPS)
+        .seed(11)
+        .build()
+        .unwrap();
+    let measures =
+        metropolis.run_with_observer(&observer.with_cadence(Cadence::every(N, N * SWEEPS / 10)));
+    analysis::mean(&measures)
+}
+
+fn assert_close(value: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (value - expected).abs() < tolerance,
+        "{value} vs {expected}"
+    );
+}
+
+/// Every model has to know its own energy difference
+fn check_delta_energy<S: Model + Clone>(mut state: S, mut params: S::Params) {
+    let mut rng = seed::seeded(3);
+    for _ in 0..200 {
+        let change = state.propose_change(&mut rng);
+        let delta = state.delta_energy(&change, &mut params).unwrap();
+        let before = state.hamiltonian(&params);
+        state.apply_change(change);
+        assert_close(state.hamiltonian(&params) - before, delta, 1e-9);
+    }
+}
+
+#[test]
+fn delta_energies_match_the_hamiltonians() {
+    let mut rng = seed::seeded(1);
+    let square = || Lattice::square(5, 4, Boundary::Periodic);
+    let ising = IsingParams { j: 1.3, h: 0.4 };
+    check_delta_energy(Ising::random(square(), &mut rng), ising);
+    check_delta_energy(Potts::random(4, square(), &mut rng), PottsParams { j: 0.7 });
+    check_delta_energy(Xy::random(square(), &mut rng), XyParams { j: 1.1, h: 0.3 });
+    let heisenberg = HeisenbergParams { j: 0.9, h: 0.2 };
+    check_delta_energy(Heisenberg::random(square(), &mut rng), heisenberg);
+    let gas = LatticeGasParams {
+        epsilon: 1.2,
+        mu: -0.4,
+    };
+    check_delta_energy(LatticeGas::random(square(), 0.3, &mut rng), gas);
+}
+
+#[test]
+fn ising_chain_energy_is_minus_tanh() {
+    let beta = 0.5;
+    let energy = sweep_average(
+        Ising::ordered(chain()),
+        IsingParams::default(),
+        beta,
+        EnergyPerSite,
+    );
+    assert_close(energy, -beta.tanh(), 0.02);
+}
+
+#[test]
+fn free_ising_spins_follow_their_field() {
+    let beta = 0.5;
+    let params = IsingParams { j: 0.0, h: 1.0 };
+    let magnetization = sweep_average(Ising::ordered(chain()), params, beta, Magnetization);
+    assert_close(magnetization, beta.tanh(), 0.02);
+}
+
+#[test]
+fn potts_chain_energy() {
+    let (beta, q) = (1.0f64, 3);
+    let energy = sweep_average(
+        Potts::ordered(q, chain()),
+        PottsParams::default(),
+        beta,
+        EnergyPerSite,
+    );
+    let expected = -beta.exp() / (beta.exp() + q as f64 - 1.0);
+    assert_close(energy, expected, 0.02);
+}
+
+/// Modified bessel function of the first kind, from its series
+fn bessel_i(n: i32, x: f64) -> f64 {
+    let mut term = (x / 2.0).powi(n) / (1..=n).product::<i32>() as f64;
+    let mut sum = 0.0;
+    for k in 1..50 {
+        sum += term;
+        term *= (x / 2.0).powi(2) / (k * (k + n)) as f64;
+    }
+    sum
+}
+
+#[test]
+fn xy_chain_energy_is_a_ratio_of_bessel_functions() {
+    let beta = 1.0;
+    let energy = sweep_average(
+        Xy::ordered(chain()),
+        XyParams::default(),
+        beta,
+        EnergyPerSite,
+    );
+    assert_close(energy, -bessel_i(1, beta) / bessel_i(0, beta), 0.02);
+}
+
+#[test]
+fn heisenberg_chain_energy_is_minus_langevin() {
+    let beta = 1.0f64;
+    let energy = sweep_average(
+        Heisenberg::ordered(chain()),
+        HeisenbergParams::default(),
+        beta,
+        EnergyPerSite,
+    );
+    assert_close(energy, -(1.0 / beta.tanh() - 1.0 / beta), 0.02);
+}
+
+#[test]
+fn ideal_lattice_gas_density_is_fermi_like() {
+    let beta = 1.0f64;
+    let params = LatticeGasParams {
+        epsilon: 0.0,
+        mu: 0.5,
+    };
+    let density = sweep_average(LatticeGas::empty(chain()), params, beta, Magnetization);
+    assert_close(density, 1.0 / (1.0 + (-beta * params.mu).exp()), 0.02);
+}
+
+/// Binder cumulant of an L x L ising at temperature t, sampled with wolff updates
+fn binder(side: usize, t: f64) -> f64 {
+    let lattice = Lattice::square(side, side, Boundary::Periodic);
+    let rng = seed::seeded(side as u64);
+    let mut wolff = Cluster::wolff(
+        Ising::ordered(lattice),
+        IsingParams::default(),
+        1.0 / t,
+        6_000,
+        rng,
+    )
+    .unwrap();
+    let magnetizations = wolff.run_with_observer(&Scheduled {
+        observer: Magnetization,
+        cadence: Cadence::every(1, 500),
+    });
+    binder_cumulant(&magnetizations)
+}
+
+#[test]
+fn ising_binder_cumulants_cross_at_the_onsager_temperature() {
+    let critical = 2.0 / (1.0 + 2f64.sqrt()).ln();
+    // larger systems are more ordered below the critical temperature, and less above it
+    let (below, above) = (critical - 0.2, critical + 0.2);
+    assert!(binder(16, below) > binder(8, below));
+    assert!(binder(16, above) < binder(8, above));
+}
+
+#[test]
+fn ising_magnetization_below_the_critical_temperature_is_onsager() {
+    let t = 2.0f64;
+    let lattice = Lattice::square(16, 16, Boundary::Periodic);
+    let mut wolff = Cluster::wolff(
+        Ising::ordered(lattice),
+        IsingParams::default(),
+        1.0 / t,
+        3_000,
+        seed::seeded(7),
+    )
+    .unwrap();
+    let magnetizations = wolff.run_with_observer(&Scheduled {
+        observer: AbsMagnetization,
+        cadence: Cadence::every(1, 300),
+    });
+    let onsager = (1.0 - (2.0 / t).sinh().powi(-4)).powf(1.0 / 8.0);
+    assert_close(analysis::mean(&magnetizations), onsager, 0.02);
+}
+
+/// Reason a wolff update refuses the params, None if it takes them
+fn cluster_refusal<S: SpinLattice>(state: S, params: S::Params) -> Option<String> {
+    match Cluster::wolff(state, params, 1.0, 10, seed::seeded(8)) {
+        Ok(_) => None,
+        Err(ClusterError::UnsupportedParams(reason)) => Some(reason),
+        Err(error) => panic!("unexpected {error}"),
+    }
+}
+
+#[test]
+fn ising_cluster_updates_refuse_a_field() {
+    let reason = cluster_refusal(Ising::ordered(chain()), IsingParams { j: 1.0, h: 0.5 });
+    assert!(reason.unwrap().contains("cluster updates ignore the field"));
+}
+
+#[test]
+fn ising_cluster_updates_refuse_antiferromagnetic_and_non_finite_couplings() {
+    for j in [-1.0, f64::NAN, f64::INFINITY] {
+        let reason = cluster_refusal(Ising::ordered(chain()), IsingParams { j, h: 0.0 });
+        assert!(reason.unwrap().contains("isn't ferromagnetic"), "j = {j}");
+    }
+    assert_eq!(
+        cluster_refusal(Ising::ordered(chain()), IsingParams::default()),
+        None
+    );
+}
+
+#[test]
+fn potts_cluster_updates_refuse_antiferromagnetic_and_non_finite_couplings() {
+    for j in [-1.0, f64::NAN, f64::NEG_INFINITY] {
+        let reason = cluster_refusal(Potts::ordered(3, chain()), PottsParams { j });
+        assert!(reason.unwrap().contains("isn't ferromagnetic"), "j = {j}");
+    }
+    assert_eq!(
+        cluster_refusal(Potts::ordered(3, chain()), PottsParams::default()),
+        None
+    );
+}
+
+#[test]
+fn potts_cluster_updates_need_two_states() {
+    for q in [0, 1] {
+        let reason = cluster_refusal(Potts::ordered(q, chain()), PottsParams::default());
+        assert!(reason.unwrap().contains("q = "), "q = {q}");
+    }
+}
@@ -0,0 +1,141 @@
+//!
+//! A state with several moves samples the same distribution,
+//! and metropolis keeps the counts of each move
+//!
+use csta::prelude::*;
+use rand::Rng;
+
+/// Harmonic oscillator, x² has a mean of 1 / (beta k)
+#[derive(Debug, Clone, PartialEq)]
+struct Oscillator {
+    x: f64,
+    /// max displacement of propose_change
+    scale: f64,
+}
+
+impl Oscillator {
+    fn at(x: f64) -> Self {
+        Self { x, scale: 0.5 }
+    }
+}
+
+impl State for Oscillator {
+    type Params = f64;
+    type Change = f64;
+
+    fn energy(&self, k: &mut Self::Params) -> f64 {
+        0.5 * *k * self.x * self.x
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        rng.random_range(-self.scale..self.scale)
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.x += change;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.x -= change;
+    }
+}
+
+impl Tunable for Oscillator {
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+}
+
+impl MultiMove for Oscillator {
+    fn moves(&self) -> MoveSet<Self> {
+        MoveSet::new()
+            .with("small", 3.0, |_, rng| rng.random_range(-0.2..0.2))
+            .with("large", 1.0, |_, rng| rng.random_range(-4.0..4.0))
+            // reflection through the origin, symmetric as well
+            .with("flip", 0.0, |state: &Oscillator, _| -2.0 * state.x)
+    }
+}
+
+#[derive(Default)]
+struct Squared;
+
+impl Observer<Oscillator> for Squared {
+    type Observation = f64;
+
+    fn measure(&self, state: &Oscillator, _params: &f64) -> Self::Observation {
+        state.x * state.x
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(10, 1_000)
+    }
+}
+
+#[test]
+fn moves_are_chosen_by_weight_and_counted() {
+    let mut metropolis = MetropolisBuilder::with_params(1.0)
+        .state(Oscillator::at(0.0))
+        .beta(2.0)
+        .steps(200_000)
+        .seed(5)
+        .build()
+        .unwrap()
+        .with_state_moves();
+    let squares = metropolis.run_with::<Squared>();
+    assert!((analysis::mean(&squares) - 0.5).abs() < 0.03);
+
+    let moves = metropolis.move_set().unwrap();
+    let (small, large) = (moves.get("small").unwrap(), moves.get("large").unwrap());
+    assert_eq!(moves.get("flip").unwrap().attempted, 0);
+    assert_eq!(small.attempted + large.attempted, 200_000);
+    assert!((small.attempted as f64 / large.attempted as f64 - 3.0).abs() < 0.1);
+    assert_eq!(small.accepted + large.accepted, metropolis.accepted_moves);
+    assert!(small.acceptance_rate() > large.acceptance_rate());
+}
+
+#[test]
+fn builder_thermalization_does_not_count_towards_the_moves() {
+    let moves = MoveSet::new().with("any", 1.0, |_: &Oscillator, rng| {
+        rng.random_range(-1.0..1.0)
+    });
+    let metropolis = Metropolis::builder()
+        .params(1.0)
+        .state(Oscillator::at(3.0))
+        .steps(10)
+        .thermalization(100)
+        .moves(moves)
+        .seed(1)
+        .build()
+        .unwrap();
+    assert_eq!(metropolis.move_set().unwrap().moves()[0].attempted, 0);
+}
+
+#[test]
+fn tuning_and_thermalization_do_not_count_towards_the_moves() {
+    let mut metropolis = Metropolis::builder()
+        .params(1.0)
+        .state(Oscillator::at(3.0))
+        .steps(5_000)
+        .seed(2)
+        .build()
+        .unwrap()
+        .with_state_moves();
+    metropolis.run_empty();
+    metropolis.tune(&Tuning::default());
+    let detector = SlidingWindow {
+        window: 100,
+        z: 2.0,
+    };
+    metropolis.thermalize(&detector, 100, 2_000);
+    metropolis.run_empty();
+
+    let moves = metropolis.move_set().unwrap();
+    let attempted: usize = moves.moves().iter().map(|m| m.attempted).sum();
+    let accepted: usize = moves.moves().iter().map(|m| m.accepted).sum();
+    assert_eq!(attempted, 10_000);
+    assert_eq!(accepted, metropolis.accepted_moves);
+}
@@ -0,0 +1,145 @@
+//!
+//! Cell and verlet lists against the brute force neighbours, in 2D and 3D,
+//! while particles move, come and go
+//!
+use csta::{models::particles::*, prelude::*};
+use rand::Rng;
+
+fn random_3d(rng: &mut impl Rng, side: Vec3f64) -> Vec3f64 {
+    Vec3f64(
+        rng.random_range(0.0..side.0),
+        rng.random_range(0.0..side.1),
+        rng.random_range(0.0..side.2),
+    )
+}
+
+fn random_2d(rng: &mut impl Rng, side: Vec2f64) -> Vec2f64 {
+    Vec2f64(rng.random_range(0.0..side.0), rng.random_range(0.0..side.1))
+}
+
+/// Every particle within the cutoff of the position is a candidate, given once
+fn check_candidates<P: Point>(cells: &CellList<P>, positions: &[P], side: P, position: P) {
+    let mut candidates: Vec<usize> = cells.candidates(position).collect();
+    candidates.sort_unstable();
+    let len = candidates.len();
+    candidates.dedup();
+    assert_eq!(len, candidates.len(), "repeated candidates");
+    let cutoff = cells.cutoff();
+    for (j, &other) in positions.iter().enumerate() {
+        if periodic_distance_squared(position, other, side) < cutoff * cutoff {
+            assert!(candidates.binary_search(&j).is_ok(), "{j} is missing");
+        }
+    }
+}
+
+/// The cells of the list hold the same particles as a freshly built one
+fn check_cells<P: Point>(cells: &CellList<P>, positions: &[P], side: P) {
+    let fresh = CellList::new(side, cells.cutoff(), positions);
+    assert_eq!(cells.len(), positions.len());
+    for cell in 0..fresh.cell_count() {
+        let mut kept = cells.particles_in(cell).to_vec();
+        let mut built = fresh.particles_in(cell).to_vec();
+        kept.sort_unstable();
+        built.sort_unstable();
+        assert_eq!(kept, built);
+    }
+}
+
+#[test]
+fn cell_lists_find_every_neighbour() {
+    let mut rng = seed::seeded(1);
+    // from a single cell to many, less than 3 along an axis repeats adjacent cells
+    for (side, cutoff) in [(4.0, 2.5), (5.0, 2.0), (10.0, 1.5)] {
+        let side = Vec3f64(side, side * 1.3, side * 0.8);
+        let positions: Vec<_> = (0..200).map(|_| random_3d(&mut rng, side)).collect();
+        let cells = CellList::new(side, cutoff, &positions);
+        for _ in 0..50 {
+            check_candidates(&cells, &positions, side, random_3d(&mut rng, side));
+        }
+
+        let side = Vec2f64(side.0, side.1);
+        let positions: Vec<_> = (0..200).map(|_| random_2d(&mut rng, side)).collect();
+        let cells = CellList::new(side, cutoff, &positions);
+        for _ in 0..50 {
+            check_candidates(&cells, &positions, side, random_2d(&mut rng, side));
+        }
+    }
+}
+
+#[test]
+fn cell_lists_follow_moves_insertions_and_removals() {
+    let mut rng = seed::seeded(2);
+    let side = Vec3f64(7.0, 6.0, 5.0);
+    let mut positions: Vec<_> = (0..100).map(|_| random_3d(&mut rng, side)).collect();
+    let mut cells = CellList::new(side, 1.2, &positions);
+    for _ in 0..1_000 {
+        match rng.random_range(0..4) {
+            0 => {
+                positions.push(random_3d(&mut rng, side));
+                assert_eq!(
+                    cells.insert(*positions.last().unwrap()),
+                    positions.len() - 1
+                );
+            }
+            1 => {
+                let index = rng.random_range(0..positions.len());
+                positions.swap_remove(index);
+                cells.remove(index);
+            }
+            _ => {
+                let index = rng.random_range(0..positions.len());
+                positions[index] = random_3d(&mut rng, side);
+                cells.move_particle(index, positions[index]);
+            }
+        }
+    }
+    check_cells(&cells, &positions, side);
+}
+
+#[test]
+fn verlet_lists_stay_valid_while_particles_move() {
+    let mut rng = seed::seeded(3);
+    let (side, cutoff) = (Vec2f64(12.0, 9.0), 1.5);
+    let mut positions: Vec<_> = (0..150).map(|_| random_2d(&mut rng, side)).collect();
+    let mut verlet = VerletList::new(side, cutoff, 0.6, &positions);
+    for _ in 0..3_000 {
+        let index = rng.random_range(0..positions.len());
+        let kick = Vec2f64(rng.random_range(-0.2..0.2), rng.random_range(-0.2..0.2));
+        let moved = positions[index] + kick;
+        positions[index] = Vec2f64(moved.0.rem_euclid(side.0), moved.1.rem_euclid(side.1));
+        verlet.update(index, &positions);
+
+        let i = rng.random_range(0..positions.len());
+        for (j, &other) in positions.iter().enumerate() {
+            if j != i && periodic_distance_squared(positions[i], other, side) < cutoff * cutoff {
+                assert!(verlet.neighbours(i).contains(&j), "{j} is missing");
+            }
+        }
+    }
+    // rebuilt now and then, not after every move
+    assert!(verlet.rebuilds() > 1 && verlet.rebuilds() < 1_000);
+}
+
+#[test]
+fn particles_sample_the_same_with_a_cell_list() {
+    let side = (100.0f64 / 0.7).cbrt();
+    let run = |particles: Particles<LennardJones>| {
+        let mut metropolis = MetropolisBuilder::with_params(LennardJones::default())
+            .state(particles)
+            .beta(1.2)
+            .steps(20_000)
+            .seed(4)
+            .build()
+            .unwrap();
+        metropolis.run_empty();
+        metropolis
+    };
+    let brute = run(Particles::grid(100, side));
+    let cells = run(Particles::grid(100, side).with_cell_list(2.5));
+    assert!(cells.state.cell_list().unwrap().cell_count() > 1);
+    assert_eq!(brute.accepted_moves, cells.accepted_moves);
+    assert_eq!(brute.state.positions, cells.state.positions);
+    let energy = cells.state.hamiltonian(&cells.params);
+    assert!((energy - brute.state.hamiltonian(&brute.params)).abs() < 1e-9 * energy.abs());
+    assert!((energy - cells.energy()).abs() < 1e-6 * energy.abs());
+}
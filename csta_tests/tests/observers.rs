@@ -0,0 +1,54 @@
+//!
+//! A tuple of observers sees the same chain as each observer run on its own,
+//! with each its own cadence, up to the 12 observers a set takes
+//!
+mod common;
+
+use common::{Energy, Magnetization, Ring, RingParams};
+use csta::prelude::*;
+
+fn seeded() -> Metropolis<Ring, SeedRng> {
+    Metropolis::builder()
+        .state(Ring::random(64, &mut seed::seeded(1)))
+        .params(RingParams { j: 1.0, h: 0.25 })
+        .beta(0.4)
+        .steps(5_000)
+        .seed(2)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn a_tuple_run_equals_each_observer_alone() {
+    let sparse = Observer::<Ring>::with_cadence(Magnetization, Cadence::every(7, 100));
+    let (energies, magnetizations, sparse_magnetizations) =
+        seeded().run_with_observers(&(Energy, Magnetization, sparse.clone()));
+
+    assert_eq!(energies, seeded().run_with_observer(&Energy));
+    assert_eq!(magnetizations, seeded().run_with_observer(&Magnetization));
+    assert_eq!(sparse_magnetizations, seeded().run_with_observer(&sparse));
+    assert!(sparse_magnetizations.len() < magnetizations.len());
+
+    let set = seeded().run_with_set::<(Energy, Magnetization)>();
+    assert_eq!(set, (energies, magnetizations));
+}
+
+#[test]
+fn a_set_takes_twelve_observers() {
+    let (first, .., last) = seeded().run_with_observers(&(
+        Energy,
+        Magnetization,
+        Energy,
+        Magnetization,
+        Energy,
+        Magnetization,
+        Energy,
+        Magnetization,
+        Energy,
+        Magnetization,
+        Energy,
+        Magnetization,
+    ));
+    assert_eq!(first, seeded().run_with_observer(&Energy));
+    assert_eq!(last, seeded().run_with_observer(&Magnetization));
+}
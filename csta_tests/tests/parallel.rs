@@ -0,0 +1,88 @@
+//!
+//! Parallel runs have to give the same results, in the same order, as serial runs
+//!
+use csta::{csta_derive::Randomizable, prelude::*};
+
+#[derive(Debug, Clone, PartialEq, Randomizable)]
+struct Walker {
+    #[csta(range(-1.0..1.0))]
+    x: f64,
+}
+
+impl State for Walker {
+    type Params = ();
+    type Change = f64;
+
+    fn energy(&self, _params: &mut Self::Params) -> f64 {
+        self.x.powi(4) - self.x * self.x
+    }
+
+    fn propose_change(&self, rng: &mut impl rand::Rng) -> Self::Change {
+        rng.random_range(-0.3..0.3)
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.x += change;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.x -= change;
+    }
+}
+
+#[derive(Default)]
+struct Position;
+
+impl Observer<Walker> for Position {
+    type Observation = f64;
+
+    fn measure(&self, state: &Walker, _params: &()) -> Self::Observation {
+        state.x
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(7, 50)
+    }
+}
+
+fn chains(seed: u64) -> Chains<Walker, SeedRng> {
+    Chains::from_samples(
+        MonteCarlo::<Walker, SeedRng>::streams(seed),
+        16,
+        |(walker, rng)| {
+            Metropolis::builder()
+                .state(walker)
+                .beta(3.0)
+                .steps(3_000)
+                .rng(rng)
+                .build()
+                .unwrap()
+        },
+    )
+}
+
+#[test]
+fn parallel_chains_match_serial_chains() {
+    let mut serial = chains(21);
+    let mut parallel = chains(21);
+    assert_eq!(
+        serial.run_with::<Position>(),
+        parallel.par_run_with::<Position>()
+    );
+    assert_eq!(serial.accepted_rates(), parallel.accepted_rates());
+}
+
+#[test]
+fn parallel_samples_match_serial_samples() {
+    let streams = MonteCarlo::<Walker, SeedRng>::streams(8);
+    let serial: Vec<Walker> = MonteCarlo::<Walker, SeedRng>::streams(8)
+        .take(100)
+        .map(|(walker, _)| walker)
+        .collect();
+    let parallel: Vec<Walker> = streams
+        .par_get(0..100)
+        .into_iter()
+        .map(|(walker, _)| walker)
+        .collect();
+    assert_eq!(serial, parallel);
+}
@@ -0,0 +1,131 @@
+//!
+//! Particles in a periodic box: minimum image distances, the energy differences
+//! of every potential, hard spheres that never overlap, and empty boxes
+//!
+use csta::{models::particles::*, prelude::*};
+
+fn assert_close(value: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (value - expected).abs() < tolerance,
+        "{value} vs {expected}"
+    );
+}
+
+#[test]
+fn distances_use_the_closest_image() {
+    let particles = Particles::<HardSphere>::new(
+        vec![Vec3f64(0.5, 1.0, 1.0), Vec3f64(3.5, 1.0, 1.0)],
+        Vec3f64(4.0, 5.0, 6.0),
+    );
+    assert_close(
+        particles.distance(particles.positions[0], particles.positions[1]),
+        1.0,
+        1e-12,
+    );
+    let image = particles.minimum_image(Vec3f64(3.0, -3.0, 2.0));
+    assert_eq!(image, Vec3f64(-1.0, 2.0, 2.0));
+    assert_eq!(
+        particles.wrap(Vec3f64(-0.5, 5.5, 13.0)),
+        Vec3f64(3.5, 0.5, 1.0)
+    );
+}
+
+#[test]
+fn potentials_vanish_at_the_cutoff() {
+    let lj = LennardJones::default();
+    assert_close(lj.pair_energy(2.5 - 1e-9), 0.0, 1e-8);
+    assert_eq!(lj.pair_energy(2.5), 0.0);
+    assert!(lj.pair_energy(2f64.powf(1.0 / 6.0)) < -0.9);
+    let yukawa = Yukawa::default();
+    assert_close(yukawa.pair_energy(3.0 - 1e-9), 0.0, 1e-8);
+    let coulomb = Coulomb::default();
+    assert_close(coulomb.pair_energy(1.0), 1.0 - 1.0 / 3.0, 1e-12);
+    let hard = HardSphere::default();
+    assert_eq!(hard.pair_energy(0.99), f64::INFINITY);
+    assert_eq!(hard.pair_energy(1.0), 0.0);
+}
+
+/// The energy difference of a displacement matches the hamiltonian
+fn check_delta_energy<P: PairPotential>(mut potential: P) {
+    let mut rng = seed::seeded(3);
+    let mut particles = Particles::<P>::grid(27, 6.0);
+    particles.scale = 1.0;
+    for _ in 0..200 {
+        let change = particles.propose_change(&mut rng);
+        let delta = particles.delta_energy(&change, &mut potential).unwrap();
+        let before = particles.hamiltonian(&potential);
+        particles.apply_change(change);
+        let after = particles.hamiltonian(&potential);
+        assert_close(
+            after - before,
+            delta,
+            1e-9 * (1.0 + before.abs() + after.abs()),
+        );
+    }
+}
+
+#[test]
+fn delta_energies_match_the_hamiltonians() {
+    check_delta_energy(LennardJones::new(1.0, 0.8));
+    check_delta_energy(Yukawa {
+        epsilon: 2.0,
+        kappa: 0.5,
+        cutoff: 2.9,
+    });
+    check_delta_energy(Coulomb {
+        strength: -0.7,
+        cutoff: 2.5,
+    });
+}
+
+#[test]
+fn hard_spheres_never_overlap() {
+    let particles = Particles::grid(64, 6.0);
+    let hard = HardSphere { diameter: 1.2 };
+    assert!(!particles.overlaps(&hard));
+    let mut metropolis = MetropolisBuilder::with_params(hard)
+        .state(particles)
+        .steps(20_000)
+        .seed(5)
+        .build()
+        .unwrap();
+    metropolis.run_empty();
+    assert!(!metropolis.state.overlaps(&metropolis.params));
+    assert!(metropolis.invalid_moves > 0);
+    assert_eq!(
+        metropolis.invalid_moves,
+        metropolis.steps - metropolis.accepted_moves
+    );
+    assert_eq!(metropolis.energy(), 0.0);
+}
+
+#[test]
+fn metropolis_keeps_the_lennard_jones_energy() {
+    let side = (64.0f64 / 0.6).cbrt();
+    let mut metropolis = MetropolisBuilder::with_params(LennardJones::default())
+        .state(Particles::grid(64, side))
+        .steps(20_000)
+        .seed(9)
+        .build()
+        .unwrap();
+    metropolis.run_empty();
+    let tracked = metropolis.energy();
+    let exact = metropolis.state.hamiltonian(&metropolis.params);
+    assert_close(tracked, exact, 1e-6 * exact.abs());
+    // a dense cold liquid is bound
+    assert!(exact / 64.0 < -2.0, "{exact}");
+}
+
+#[test]
+fn moves_in_an_empty_box_are_invalid() {
+    let mut metropolis = MetropolisBuilder::with_params(LennardJones::default())
+        .state(Particles::new(Vec::new(), Vec3f64(4.0, 4.0, 4.0)))
+        .steps(100)
+        .seed(6)
+        .build()
+        .unwrap();
+    metropolis.run_empty();
+    assert_eq!(metropolis.invalid_moves, 100);
+    assert_eq!(metropolis.accepted_moves, 0);
+    assert_eq!(metropolis.energy(), 0.0);
+}
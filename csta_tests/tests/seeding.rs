@@ -0,0 +1,140 @@
+//!
+//! Same seed, same run: these check that seeded runs are bit identical
+//!
+use csta::{csta_derive::Randomizable, prelude::*};
+
+#[derive(Debug, Clone, Copy, PartialEq, Randomizable)]
+enum Spin {
+    Up,
+    Down,
+}
+
+impl Spin {
+    fn flip(&mut self) {
+        *self = match self {
+            Spin::Up => Spin::Down,
+            Spin::Down => Spin::Up,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Randomizable)]
+struct Chain {
+    #[csta(len(64))]
+    spins: Vec<Spin>,
+}
+
+impl State for Chain {
+    type Params = ();
+    type Change = usize;
+
+    fn energy(&self, _params: &mut Self::Params) -> f64 {
+        let n = self.spins.len();
+        (0..n)
+            .map(|i| {
+                if self.spins[i] == self.spins[(i + 1) % n] {
+                    -1.0
+                } else {
+                    1.0
+                }
+            })
+            .sum()
+    }
+
+    fn propose_change(&self, rng: &mut impl rand::Rng) -> Self::Change {
+        rng.random_range(0..self.spins.len())
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.spins[change].flip();
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.spins[change].flip();
+    }
+}
+
+#[derive(Default)]
+struct Energy;
+
+impl Observer<Chain> for Energy {
+    type Observation = f64;
+
+    fn measure(&self, state: &Chain, params: &()) -> Self::Observation {
+        state.energy(&mut params.clone())
+    }
+
+    fn cadence(&self) -> Cadence {
+        Cadence::every(5, 0)
+    }
+}
+
+fn seeded_run(seed: u64) -> (Vec<f64>, usize) {
+    let mut metropolis = Metropolis::builder()
+        .seed(seed)
+        .sample_state()
+        .beta(0.7)
+        .steps(5_000)
+        .thermalization(100)
+        .build()
+        .unwrap();
+    (metropolis.run_with::<Energy>(), metropolis.accepted_moves)
+}
+
+#[test]
+fn same_seed_same_observations() {
+    assert_eq!(seeded_run(42), seeded_run(42));
+}
+
+#[test]
+fn different_seed_different_observations() {
+    assert_ne!(seeded_run(42), seeded_run(43));
+}
+
+#[test]
+fn builder_seed_is_reproducible() {
+    let state = MonteCarlo::<Chain, _>::with_seed(7).next().unwrap();
+    let build = || {
+        Metropolis::builder()
+            .state(state.clone())
+            .steps(2_000)
+            .seed(11)
+            .build()
+            .unwrap()
+    };
+    let (mut a, mut b) = (build(), build());
+    assert_eq!(a.run_with::<Energy>(), b.run_with::<Energy>());
+    assert_eq!(a.state, b.state);
+}
+
+#[test]
+fn montecarlo_streams_do_not_depend_on_order() {
+    let mut streams = MonteCarlo::<Chain, SeedRng>::streams(3);
+    let (fourth, _) = streams.nth(4).unwrap();
+    let (first, _) = MonteCarlo::<Chain, SeedRng>::streams(3).next().unwrap();
+
+    let all: Vec<Chain> = MonteCarlo::<Chain, SeedRng>::streams(3)
+        .take(5)
+        .map(|(chain, _)| chain)
+        .collect();
+    assert_eq!(all[0], first);
+    assert_eq!(all[4], fourth);
+    assert_ne!(all[0], all[1]);
+}
+
+#[test]
+fn stream_chains_are_reproducible() {
+    let run = |index| {
+        let (chain, rng) = MonteCarlo::<Chain, SeedRng>::streams(5).get(index);
+        let mut metropolis = Metropolis::builder()
+            .state(chain)
+            .beta(0.5)
+            .steps(2_000)
+            .rng(rng)
+            .build()
+            .unwrap();
+        metropolis.run_with::<Energy>()
+    };
+    assert_eq!(run(2), run(2));
+    assert_ne!(run(1), run(2));
+}
@@ -0,0 +1,138 @@
+//!
+//! The lines written by the csv and json lines sinks, the first io error
+//! returned by finish, and closures called with each step
+//!
+mod common;
+
+use std::io::{self, Write};
+
+use common::{Energy, Ring};
+use csta::prelude::*;
+
+/// Takes `left` bytes, then fails every write
+struct Full {
+    written: Vec<u8>,
+    left: usize,
+    failures: usize,
+}
+
+impl Full {
+    fn after(left: usize) -> Self {
+        Self {
+            written: Vec::new(),
+            left,
+            failures: 0,
+        }
+    }
+}
+
+impl Write for Full {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.left == 0 {
+            self.failures += 1;
+            return Err(io::Error::other("full"));
+        }
+        let len = buf.len().min(self.left);
+        self.written.extend_from_slice(&buf[..len]);
+        self.left -= len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn csv_lines_are_step_and_observation() {
+    let mut sink = CsvSink::new(Vec::new(), "energy");
+    sink.push(0, -1.5);
+    sink.push(10, 2.0);
+    sink.push(20, 0.25);
+    let written = String::from_utf8(sink.finish().unwrap()).unwrap();
+    assert_eq!(written, "step,energy\n0,-1.5\n10,2\n20,0.25\n");
+}
+
+#[test]
+fn csv_of_a_run_has_every_observation() {
+    let metropolis = || {
+        Metropolis::builder()
+            .state(Ring::random(16, &mut seed::seeded(1)))
+            .beta(0.4)
+            .steps(200)
+            .seed(2)
+            .build()
+            .unwrap()
+    };
+    let mut sink = CsvSink::new(Vec::new(), "energy");
+    metropolis().run_with_sink(&Energy, &mut sink);
+    let written = String::from_utf8(sink.finish().unwrap()).unwrap();
+
+    let mut expected = Vec::new();
+    metropolis().run_with_sink(
+        &Energy,
+        &mut from_fn(|step, energy: f64| {
+            expected.push(format!("{step},{energy}"));
+        }),
+    );
+    let mut lines = written.lines();
+    assert_eq!(lines.next(), Some("step,energy"));
+    assert_eq!(lines.collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn csv_returns_the_first_error() {
+    // the header fails
+    let sink = CsvSink::new(Full::after(0), "energy");
+    assert_eq!(sink.finish().err().unwrap().to_string(), "full");
+
+    // a line fails half way, the ones after aren't written
+    let mut writer = Full::after(15);
+    let mut sink = CsvSink::new(&mut writer, "energy");
+    sink.push(0, 1.0);
+    sink.push(1, 2.0);
+    sink.push(2, 3.0);
+    let error = sink.finish().err().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::Other);
+    assert_eq!(writer.written, b"step,energy\n0,1");
+    assert_eq!(writer.failures, 1);
+}
+
+#[test]
+fn json_lines_are_step_and_value() {
+    let mut sink = JsonLinesSink::new(Vec::new());
+    sink.push(0, -1.5);
+    sink.push(10, 2.0);
+    sink.push(20, 0.25);
+    let written = String::from_utf8(sink.finish().unwrap()).unwrap();
+    assert_eq!(
+        written,
+        "{\"step\":0,\"value\":-1.5}\n{\"step\":10,\"value\":2.0}\n{\"step\":20,\"value\":0.25}\n"
+    );
+
+    let mut sink = JsonLinesSink::new(Vec::new());
+    sink.push(3, (1, "up"));
+    let written = String::from_utf8(sink.finish().unwrap()).unwrap();
+    assert_eq!(written, "{\"step\":3,\"value\":[1,\"up\"]}\n");
+}
+
+#[test]
+fn json_lines_return_the_first_error() {
+    let mut writer = Full::after(20);
+    let mut sink = JsonLinesSink::new(&mut writer);
+    sink.push(0, 1.0);
+    sink.push(1, 2.0);
+    sink.push(2, 3.0);
+    assert_eq!(sink.finish().err().unwrap().to_string(), "full");
+    assert_eq!(writer.written, b"{\"step\":0,\"value\":1.");
+    assert_eq!(writer.failures, 1);
+}
+
+#[test]
+fn fn_sink_is_called_with_each_step() {
+    let mut seen = Vec::new();
+    let mut sink = from_fn(|step, observation: f64| seen.push((step, observation)));
+    sink.push(0, 1.0);
+    sink.push(5, -2.0);
+    assert_eq!(seen, [(0, 1.0), (5, -2.0)]);
+}
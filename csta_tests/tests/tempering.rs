@@ -0,0 +1,94 @@
+//!
+//! Replica exchange: swaps accepted with min(1, exp(Δβ ΔE)),
+//! and seeded ladders give the same observations
+//!
+mod common;
+
+use common::{Energy, Ring};
+use csta::prelude::*;
+
+/// A state stuck at an energy, metropolis never changes it
+#[derive(Debug, Clone, PartialEq)]
+struct Stuck {
+    energy: f64,
+}
+
+impl State for Stuck {
+    type Params = ();
+    type Change = ();
+
+    fn energy(&self, _params: &mut Self::Params) -> f64 {
+        self.energy
+    }
+
+    fn propose_change(&self, _rng: &mut impl rand::Rng) -> Self::Change {}
+
+    fn apply_change(&mut self, _change: Self::Change) {}
+
+    fn revert_change(&mut self, _change: Self::Change) {}
+}
+
+/// Rate of the swaps between a replica at energy `a` and beta 1 and one at energy `b` and beta 2
+fn swap_rate(a: f64, b: f64) -> f64 {
+    let replica = |beta, seed| {
+        Metropolis::builder()
+            .state(Stuck { energy: 0.0 })
+            .beta(beta)
+            .steps(1)
+            .seed(seed)
+            .build()
+            .unwrap()
+    };
+    let mut tempering = ParallelTempering::new(
+        vec![replica(1.0, 1), replica(2.0, 2)],
+        1,
+        1,
+        seed::seeded(3),
+    );
+    for _ in 0..40_000 {
+        // put the energies back, an accepted swap exchanged them
+        tempering.replicas[0].state.energy = a;
+        tempering.replicas[1].state.energy = b;
+        for replica in tempering.replicas.iter_mut() {
+            replica.refresh_energy();
+        }
+        tempering.swap();
+    }
+    tempering.swap_acceptance_rates()[0]
+}
+
+#[test]
+fn swaps_are_accepted_with_the_exchange_probability() {
+    for (a, b) in [(1.0, 0.0), (0.5, -0.5), (3.0, 1.0), (0.0, 1.0f64)] {
+        let expected = ((2.0 - 1.0) * (b - a)).exp().min(1.0);
+        let rate = swap_rate(a, b);
+        assert!((rate - expected).abs() < 0.01, "{rate} vs {expected}");
+    }
+}
+
+/// Four replicas with the first streams of `seed`, and the next stream for the swaps
+fn tempering(seed: u64) -> ParallelTempering<Ring, SeedRng> {
+    let replicas: Vec<_> = (0..4)
+        .map(|i| {
+            let mut rng = seed::stream(seed, i);
+            Metropolis::builder()
+                .state(Ring::random(32, &mut rng))
+                .beta(0.2 + 0.1 * i as f64)
+                .steps(1)
+                .rng(rng)
+                .build()
+                .unwrap()
+        })
+        .collect();
+    let swaps = seed::stream(seed, replicas.len() as u64);
+    ParallelTempering::new(replicas, 3_000, 10, swaps)
+}
+
+#[test]
+fn seeded_ladders_are_reproducible() {
+    let run = |seed| tempering(seed).run_with::<Energy>();
+    let observations = run(4);
+    assert_eq!(observations.len(), 4);
+    assert_eq!(observations, run(4));
+    assert_ne!(observations, run(5));
+}
@@ -0,0 +1,86 @@
+//!
+//! The equilibration detectors on an exponential decay plus noise: they wait
+//! for the decay to die out, and never decide on fewer energies than they need
+//!
+mod common;
+
+use common::Ring;
+use csta::prelude::*;
+use rand::Rng;
+
+/// 10 exp(-t / tau) plus uniform noise in (-noise, noise)
+fn decay(tau: f64, noise: f64, len: usize, seed: u64) -> Vec<f64> {
+    let mut rng = seed::seeded(seed);
+    (0..len)
+        .map(|t| 10.0 * (-(t as f64) / tau).exp() + rng.random_range(-noise..noise))
+        .collect()
+}
+
+/// First length, in steps of `every`, of the trace at which the detector declares equilibrium
+fn detected(detector: &impl Equilibration, trace: &[f64], every: usize) -> Option<usize> {
+    (every..=trace.len())
+        .step_by(every)
+        .find(|&n| detector.equilibrated(&trace[..n]))
+}
+
+#[test]
+fn sliding_window_waits_for_the_decay() {
+    let trace = decay(500.0, 1.0, 20_000, 1);
+    let detector = SlidingWindow {
+        window: 500,
+        z: 2.0,
+    };
+    let n = detected(&detector, &trace, 100).unwrap();
+    // the previous window has to be past a few decay times, where 10 exp(-t/500) is under the noise
+    assert!(n >= 2_500, "{n}");
+    assert!(n <= 6_000, "{n}");
+}
+
+#[test]
+fn geweke_waits_for_the_decay() {
+    let trace = decay(20.0, 5.0, 20_000, 2);
+    let n = detected(&Geweke::default(), &trace, 100).unwrap();
+    // the decay biases the mean of the first tenth, until it spans several decay times
+    assert!(n >= 1_000, "{n}");
+    assert!(n <= 10_000, "{n}");
+}
+
+#[test]
+fn detectors_need_enough_energies() {
+    let flat = vec![0.0; 10_000];
+    let short = SlidingWindow {
+        window: MIN_ENERGIES - 1,
+        z: 2.0,
+    };
+    assert!(!short.equilibrated(&flat));
+    let long = SlidingWindow {
+        window: MIN_ENERGIES,
+        z: 2.0,
+    };
+    assert!(!long.equilibrated(&flat[..2 * MIN_ENERGIES - 1]));
+    assert!(long.equilibrated(&flat[..2 * MIN_ENERGIES]));
+
+    assert!(!Geweke::default().equilibrated(&flat[..10 * MIN_ENERGIES - 1]));
+    assert!(Geweke::default().equilibrated(&flat[..10 * MIN_ENERGIES]));
+}
+
+#[test]
+fn thermalize_gives_up_within_max_steps() {
+    let mut metropolis = Metropolis::builder()
+        .state(Ring::random(64, &mut seed::seeded(3)))
+        .beta(0.3)
+        .steps(1)
+        .seed(4)
+        .build()
+        .unwrap();
+    let detector = SlidingWindow {
+        window: MIN_ENERGIES,
+        z: 2.0,
+    };
+    assert_eq!(
+        metropolis.thermalize(&detector, 10, 2 * MIN_ENERGIES - 1),
+        None
+    );
+    let steps = metropolis.thermalize(&detector, 10, 100_000).unwrap();
+    assert!(steps >= 2 * MIN_ENERGIES);
+}
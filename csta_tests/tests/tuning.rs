@@ -0,0 +1,111 @@
+//!
+//! Tuning reaches its target acceptance rate before freezing the scale,
+//! and refuses settings it can't tune with
+//!
+use csta::prelude::*;
+use rand::Rng;
+
+/// Harmonic oscillator whose proposals are scaled
+#[derive(Debug, Clone, PartialEq)]
+struct Oscillator {
+    x: f64,
+    /// max displacement of propose_change
+    scale: f64,
+}
+
+impl State for Oscillator {
+    type Params = f64;
+    type Change = f64;
+
+    fn energy(&self, k: &mut Self::Params) -> f64 {
+        0.5 * *k * self.x * self.x
+    }
+
+    fn propose_change(&self, rng: &mut impl Rng) -> Self::Change {
+        rng.random_range(-self.scale..self.scale)
+    }
+
+    fn apply_change(&mut self, change: Self::Change) {
+        self.x += change;
+    }
+
+    fn revert_change(&mut self, change: Self::Change) {
+        self.x -= change;
+    }
+}
+
+impl Tunable for Oscillator {
+    fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+}
+
+fn metropolis(steps: usize, seed: u64) -> Metropolis<Oscillator, SeedRng> {
+    Metropolis::builder()
+        .params(1.0)
+        .state(Oscillator { x: 0.0, scale: 0.5 })
+        .steps(steps)
+        .seed(seed)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn tuning_reaches_the_target_and_production_keeps_the_scale() {
+    for target in [0.3, 0.5, 0.7] {
+        let mut metropolis = metropolis(50_000, 3);
+        let tuning = Tuning {
+            batch: 1_000,
+            batches: 100,
+            ..Tuning::with_target(target)
+        };
+        metropolis.tune(&tuning);
+        let scale = metropolis.state.scale();
+        assert_eq!(metropolis.accepted_moves, 0);
+
+        metropolis.run_empty();
+        let rate = metropolis.accepted_rate();
+        assert!((rate - target).abs() < 0.02, "{rate} vs {target}");
+        assert_eq!(metropolis.state.scale(), scale);
+    }
+}
+
+#[test]
+fn the_scale_stays_within_its_bounds() {
+    let mut metropolis = metropolis(10, 5);
+    // an acceptance rate this low needs a larger scale than the bound
+    metropolis.tune(&Tuning {
+        max_scale: 0.6,
+        ..Tuning::with_target(0.05)
+    });
+    assert_eq!(metropolis.state.scale(), 0.6);
+}
+
+#[test]
+#[should_panic(expected = "tuning needs steps in a batch")]
+fn tuning_needs_steps_in_a_batch() {
+    metropolis(10, 4).tune(&Tuning {
+        batch: 0,
+        ..Tuning::default()
+    });
+}
+
+#[test]
+#[should_panic(expected = "target acceptance rate must be in (0, 1)")]
+fn the_target_is_a_rate() {
+    metropolis(10, 4).tune(&Tuning::with_target(1.0));
+}
+
+#[test]
+#[should_panic(expected = "scale bounds must be positive and ordered")]
+fn the_scale_bounds_are_ordered() {
+    metropolis(10, 4).tune(&Tuning {
+        min_scale: 2.0,
+        max_scale: 1.0,
+        ..Tuning::default()
+    });
+}
@@ -0,0 +1,61 @@
+//!
+//! Wang-Landau against the exact density of states of a periodic ising chain,
+//! g(E) = 2 C(N, k) with k domain walls and E = 2k - N,
+//! and walks that can't get flat give up
+//!
+mod common;
+
+use common::{Ring, RingParams};
+use csta::prelude::*;
+
+const N: usize = 10;
+
+fn chain() -> Ring {
+    Ring::random(N, &mut seed::seeded(1))
+}
+
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+#[test]
+fn ising_chain_density_of_states_is_exact() {
+    let n = N as f64;
+    // an even number of domain walls, the energies are 4 apart
+    let bins = UniformBins::discrete(-n, n, 4.0);
+    let mut wang_landau = WangLandau::new(chain(), RingParams::default(), bins, seed::seeded(2));
+    wang_landau.ln_f_final = 1e-6;
+    wang_landau.flatness = 0.95;
+    let mut density = wang_landau.run().unwrap();
+    density.normalize(n * 2f64.ln());
+
+    assert_eq!(density.energies.len(), N / 2 + 1);
+    for (energy, ln_g) in density.energies.iter().zip(&density.ln_g) {
+        let walls = ((energy + n) / 2.0).round() as usize;
+        let exact = 2f64.ln() + ln_binomial(N, walls);
+        assert!((ln_g - exact).abs() < 0.1, "at {energy}: {ln_g} vs {exact}");
+    }
+}
+
+#[test]
+fn walks_out_of_the_range_give_up() {
+    let bins = UniformBins::new(100.0, 200.0, 10);
+    let mut wang_landau = WangLandau::new(chain(), RingParams::default(), bins, seed::seeded(3));
+    wang_landau.check_every = 100;
+    wang_landau.max_checks = 20;
+    let not_flat = wang_landau.run().unwrap_err();
+    assert_eq!(not_flat.ln_f, 1.0);
+    assert!(not_flat.partial.energies.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn uniform_bins_need_a_bin() {
+    UniformBins::new(0.0, 1.0, 0);
+}
+
+#[test]
+#[should_panic]
+fn discrete_bins_need_a_positive_spacing() {
+    UniformBins::discrete(0.0, 1.0, 0.0);
+}